@@ -0,0 +1,329 @@
+use std::str::FromStr;
+
+use rand::{ rngs::StdRng, Rng, SeedableRng };
+
+use crate::{
+    ant::{
+        brood::Caste,
+        colony::Colony,
+        designation::{ Area, Designation },
+        jobs::JobKind,
+        spatial::SpatialIndex,
+    },
+    chunk::{ tile::TileFlag, ChunkManager, HEIGHT },
+    time::TICKS_PER_MINUTE,
+};
+
+type Pos = (i32, i32, i32);
+
+/// Ants a row of chamber can house, the nest grows when there are more
+const ANTS_PER_ROW: usize = 6;
+/// Chamber rows dug east of the nest plan are that wide on each side
+const ROW_HALF_WIDTH: i32 = 2;
+/// Below this much food, the colony needs workers before anything else
+const LOW_FOOD: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Ticks between two decisions
+    fn decision_ticks(self) -> u64 {
+        match self {
+            Difficulty::Easy => TICKS_PER_MINUTE,
+            Difficulty::Normal => TICKS_PER_MINUTE / 2,
+            Difficulty::Hard => TICKS_PER_MINUTE / 4,
+        }
+    }
+
+    /// Soldiers gathered before going on a raid
+    fn raid_size(self) -> usize {
+        match self {
+            Difficulty::Easy => 8,
+            Difficulty::Normal => 5,
+            Difficulty::Hard => 3,
+        }
+    }
+
+    /// How far from the queen the colony forages and defends
+    fn territory(self) -> i32 {
+        match self {
+            Difficulty::Easy => 8,
+            Difficulty::Normal => 12,
+            Difficulty::Hard => 16,
+        }
+    }
+
+    /// Explorers and soldiers standing at the edge of the territory
+    fn guard_posts(self) -> usize {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 4,
+        }
+    }
+
+    /// Share of soldiers in peace time
+    fn soldier_share(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.05,
+            Difficulty::Normal => 0.1,
+            Difficulty::Hard => 0.2,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("Unknown difficulty {:?}", s)),
+        }
+    }
+}
+
+/// Plays a colony like a player would : through designations, jobs and raids
+pub struct Controller {
+    pub difficulty: Difficulty,
+    rng: StdRng,
+    last_decision: Option<u64>,
+    /// Chamber rows designated so far
+    expansions: i32,
+}
+
+impl Controller {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self { difficulty, rng: StdRng::seed_from_u64(seed), last_decision: None, expansions: 0 }
+    }
+
+    /// `enemies` are the ants of every other colony
+    pub fn tick(&mut self, colony: &mut Colony, chunk_mngr: &ChunkManager, enemies: &SpatialIndex, tick: u64) {
+        if self.last_decision.is_some_and(|last| tick < last + self.difficulty.decision_ticks()) {
+            return;
+        }
+        self.last_decision = Some(tick);
+
+        let threat = self.closest_threat(colony, enemies);
+        self.plan_castes(colony, threat.is_some());
+        self.expand(colony, chunk_mngr);
+        self.forage(colony, chunk_mngr);
+        self.guard(colony, chunk_mngr);
+        self.fight(colony, chunk_mngr, enemies, threat);
+    }
+
+    /// Enemy ant inside the (explored) territory, if any
+    fn closest_threat(&self, colony: &Colony, enemies: &SpatialIndex) -> Option<Pos> {
+        let (qx, qy, _) = colony.queen.pos;
+        let territory = self.difficulty.territory();
+
+        enemies
+            .around(colony.queen.pos, territory)
+            .filter(|(x, y, _)| colony.explored.is_explored((*x, *y)))
+            .min_by_key(|(x, y, _)| (x - qx).abs() + (y - qy).abs())
+    }
+
+    /// Soldiers when threatened, workers when hungry
+    fn plan_castes(&self, colony: &mut Colony, threatened: bool) {
+        let soldiers = if threatened { 0.3 } else { self.difficulty.soldier_share() };
+        let workers = if colony.food < LOW_FOOD { 0.7 } else { 0.55 };
+        let explorers = 0.15;
+        let nurses = (1.0 - soldiers - workers - explorers).max(0.1);
+
+        colony.caste_targets = [
+            (Caste::Worker, workers),
+            (Caste::Nurse, nurses),
+            (Caste::Explorer, explorers),
+            (Caste::Soldier, soldiers),
+        ];
+    }
+
+    /// Digs one more chamber row once the nest is full
+    fn expand(&mut self, colony: &mut Colony, chunk_mngr: &ChunkManager) {
+        let Some(plan) = &colony.nest_plan else {
+            return;
+        };
+        let is_digging =
+            plan.next(chunk_mngr).is_some() ||
+            colony.designations.jobs.iter().any(|job| job.kind == Designation::Dig);
+        let population = colony.ants.len() + colony.brood.len();
+        if is_digging || population <= ANTS_PER_ROW * ((self.expansions + 1) as usize) {
+            return;
+        }
+
+        // East end of the chamber
+        let Some((x, _, z)) = plan.digs.iter().map(|dig| dig.tile).max_by_key(|tile| tile.0) else {
+            return;
+        };
+        let y = colony.queen.pos.1;
+        let x = x + 1 + self.expansions;
+        let row = Area::new((x, y - ROW_HALF_WIDTH, z), (x, y + ROW_HALF_WIDTH, z));
+
+        colony.designate(row, Designation::Dig, chunk_mngr);
+        self.expansions += 1;
+    }
+
+    /// Sends workers to the food the explorers found around the nest
+    fn forage(&self, colony: &mut Colony, chunk_mngr: &ChunkManager) {
+        let (qx, qy, _) = colony.queen.pos;
+        let territory = self.difficulty.territory();
+        // The whole territory read at once, each chunk is locked once
+        let from = (qx - territory, qy - territory, 0);
+        let to = (qx + territory, qy + territory, (HEIGHT as i32) - 1);
+        let tiles = chunk_mngr.tiles_in(from, to);
+        let side = 2 * territory + 1;
+        let tile_at = |(x, y, z): Pos| {
+            (z <= to.2).then(|| tiles[((x - from.0) + (y - from.1) * side + z * side * side) as usize]).flatten()
+        };
+        let is_ground = |p: Pos| tile_at(p).is_some_and(|tile| !tile.properties.contains(TileFlag::TRAVERSABLE));
+
+        for x in from.0..=to.0 {
+            for y in from.1..=to.1 {
+                if !colony.explored.is_explored((x, y)) {
+                    continue;
+                }
+                // Top of the ground, like ChunkManager::surface_z
+                let Some(z) = (0..=to.2).rev().find(|z| is_ground((x, y, *z))) else {
+                    continue;
+                };
+                let food = (x, y, z + 1);
+                if tile_at(food).is_some_and(|tile| tile.food_quantity() > 0) {
+                    colony.jobs.post(JobKind::Forage, food, food, JobKind::Forage.priority());
+                }
+            }
+        }
+    }
+
+    /// Guard posts at the edge of the territory, placed once
+    fn guard(&mut self, colony: &mut Colony, chunk_mngr: &ChunkManager) {
+        if colony.jobs.jobs.iter().any(|job| job.kind == JobKind::Guard) {
+            return;
+        }
+        let (qx, qy, _) = colony.queen.pos;
+        let territory = self.difficulty.territory();
+
+        for _ in 0..self.difficulty.guard_posts() {
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            let x = qx + ((angle.cos() * (territory as f32)) as i32);
+            let y = qy + ((angle.sin() * (territory as f32)) as i32);
+            let Some(z) = chunk_mngr.surface_z((x, y)) else {
+                continue;
+            };
+            let post = (x, y, z + 1);
+            colony.jobs.post(JobKind::Guard, post, post, JobKind::Guard.priority());
+        }
+    }
+
+    /// Defends the queen first, raids when strong enough
+    fn fight(
+        &mut self,
+        colony: &mut Colony,
+        chunk_mngr: &ChunkManager,
+        enemies: &SpatialIndex,
+        threat: Option<Pos>
+    ) {
+        let soldiers = colony.ants
+            .castes()
+            .iter()
+            .filter(|caste| **caste == Caste::Soldier)
+            .count();
+
+        if let Some(threat) = threat {
+            colony.raid(threat, chunk_mngr);
+            return;
+        }
+
+        let (qx, qy, _) = colony.queen.pos;
+        let target = enemies
+            .iter()
+            .filter(|(x, y, _)| colony.explored.is_explored((*x, *y)))
+            .min_by_key(|(x, y, _)| (x - qx).abs() + (y - qy).abs());
+        match target {
+            Some(target) if soldiers >= self.difficulty.raid_size() => {
+                colony.raid(target, chunk_mngr);
+            }
+            // Too many losses, back home
+            _ if soldiers < self.difficulty.raid_size() / 2 => {
+                colony.call_back();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{
+            ai::{ Controller, Difficulty },
+            brood::Caste,
+            colony::Colony,
+            jobs::JobKind,
+            spatial::SpatialIndex,
+        },
+        chunk::{ tile::Tile, ChunkManager },
+    };
+
+    fn play(difficulty: Difficulty, seed: u64) -> Colony {
+        let mngr = ChunkManager::flat_world();
+        mngr.set_tile((9, 3, 2), Tile::GRASS);
+
+        let mut colony = Colony::new("AI", (2, 2, 2));
+        // The explorers did their job
+        colony.explored.reveal_around((8, 4, 2), 8);
+        for _ in 0..4 {
+            colony.recruit(Caste::Soldier.hatch((2, 2, 2)));
+        }
+        colony.recruit(Caste::Worker.hatch((2, 2, 2)));
+
+        let mut ai = Controller::new(difficulty, seed);
+        ai.tick(&mut colony, &mngr, &SpatialIndex::new([(14, 6, 2)]), 0);
+        colony
+    }
+
+    #[test]
+    fn same_seed_same_decisions() {
+        let jobs = |colony: &Colony| {
+            colony.jobs.jobs.iter().map(|job| (job.kind, job.target)).collect::<Vec<_>>()
+        };
+        assert_eq!(jobs(&play(Difficulty::Hard, 7)), jobs(&play(Difficulty::Hard, 7)));
+
+        let colony = play(Difficulty::Hard, 7);
+        assert!(colony.jobs.jobs.iter().any(|job| job.kind == JobKind::Forage && job.target == (9, 3, 2)));
+
+        // Close enough to be a threat for the hard AI, not for the easy one
+        assert_eq!(colony.rally, Some((14, 6, 2)));
+        assert_eq!(colony.caste_targets[3], (Caste::Soldier, 0.3));
+        let easy = play(Difficulty::Easy, 7);
+        assert_eq!(easy.rally, None, "Not enough soldiers to raid");
+        assert!(easy.jobs.jobs.iter().all(|job| job.kind != JobKind::Guard));
+    }
+
+    #[test]
+    fn the_ai_only_knows_what_was_explored() {
+        let mngr = ChunkManager::flat_world();
+        mngr.set_tile((12, 3, 2), Tile::GRASS);
+        let mut colony = Colony::new("AI", (2, 2, 2));
+        for _ in 0..4 {
+            colony.recruit(Caste::Soldier.hatch((2, 2, 2)));
+        }
+
+        let mut ai = Controller::new(Difficulty::Hard, 0);
+        ai.tick(&mut colony, &mngr, &SpatialIndex::new([(14, 6, 2)]), 0);
+        assert_eq!(colony.rally, None);
+        assert!(colony.jobs.jobs.iter().all(|job| job.kind != JobKind::Forage));
+
+        colony.explored.reveal_around((13, 5, 2), 2);
+        ai.tick(&mut colony, &mngr, &SpatialIndex::new([(14, 6, 2)]), Difficulty::Hard.decision_ticks());
+        assert_eq!(colony.rally, Some((14, 6, 2)));
+        assert!(colony.jobs.jobs.iter().any(|job| job.kind == JobKind::Forage && job.target == (12, 3, 2)));
+    }
+}
@@ -0,0 +1,498 @@
+use std::sync::OnceLock;
+
+use rand::{ rngs::StdRng, Rng };
+use serde::Deserialize;
+
+use crate::{
+    ant::{
+        apply_gravity,
+        brood::Caste,
+        combat,
+        direction::Direction,
+        is_within_reach,
+        jobs::{ Job, JobKind },
+        needs::Needs,
+        perception::{ Memory, Perception },
+        pheromone::Pheromone,
+        Action,
+        Cargo,
+        Context,
+    },
+    chunk::ChunkManager,
+};
+
+type Pos = (i32, i32, i32);
+
+/// What a tree knows about the ant thinking with it
+pub struct Blackboard<'a> {
+    pub ctx: &'a Context<'a>,
+    pub pos: Pos,
+    pub carrying: Option<Cargo>,
+    pub job: Option<Job>,
+    pub needs: Option<Needs>,
+    /// Goal of the path being followed, until it's done
+    pub heading: Option<Pos>,
+    /// The last path could not reach its goal
+    pub is_lost: bool,
+    pub seen: &'a Perception,
+    pub memory: Memory,
+    /// Dice for this decision only
+    pub rng: StdRng,
+}
+
+/// Trees are loaded from `assets/behaviors`, one file per caste
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Node {
+    /// First child that doesn't fail
+    Selector(Vec<Node>),
+    /// Children in order, until one doesn't succeed
+    Sequence(Vec<Node>),
+    If(Condition),
+    Do(Task),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    HasJob(JobKind),
+    IsCarrying,
+    IsLost,
+}
+
+/// Leaves of the trees, also shown in the debug view (B)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Task {
+    /// Hits an enemy standing next to the ant
+    FightBack,
+    /// Runs at the closest enemy in sight, or last seen
+    Charge,
+    GoToRally,
+    /// Brings its cargo where it belongs, then drops it
+    BringCargo,
+    /// Eats or rests when needed
+    Tend,
+    /// Its job from the colony board
+    Work,
+    /// Food within reach
+    PickUpFood,
+    /// Goes to the closest food in sight, or last seen
+    GoToFood,
+    /// Follows the food trail left by the others
+    FollowFood,
+    /// Walks back the way it came
+    Retrace,
+    Wander,
+    /// Walks somewhere new
+    Explore,
+    /// Wanders around the queen
+    Patrol,
+    /// Stays with the queen
+    StayHome,
+}
+
+pub enum Status {
+    Failure,
+    /// Nothing more to do for now
+    Success,
+    Running(Action),
+}
+
+impl Node {
+    /// `current` is the last task that didn't fail
+    pub fn run(&self, board: &mut Blackboard, current: &mut Option<Task>) -> Status {
+        match self {
+            Node::Selector(children) => {
+                children
+                    .iter()
+                    .map(|child| child.run(board, current))
+                    .find(|status| !matches!(status, Status::Failure))
+                    .unwrap_or(Status::Failure)
+            }
+            Node::Sequence(children) => {
+                children
+                    .iter()
+                    .map(|child| child.run(board, current))
+                    .find(|status| !matches!(status, Status::Success))
+                    .unwrap_or(Status::Success)
+            }
+            Node::If(condition) => {
+                if condition.holds(board) { Status::Success } else { Status::Failure }
+            }
+            Node::Do(task) => {
+                let status = task.run(board);
+                if !matches!(status, Status::Failure) {
+                    *current = Some(*task);
+                }
+                status
+            }
+        }
+    }
+
+    /// What to do, and the task that decided it
+    pub fn decide(&self, board: &mut Blackboard) -> (Option<Action>, Option<Task>) {
+        let mut current = None;
+        match self.run(board, &mut current) {
+            Status::Running(action) => (Some(action), current),
+            Status::Success => (None, current),
+            Status::Failure => (None, None),
+        }
+    }
+}
+
+impl Condition {
+    fn holds(self, board: &Blackboard) -> bool {
+        match self {
+            Condition::HasJob(kind) => board.job.is_some_and(|job| job.kind == kind),
+            Condition::IsCarrying => board.carrying.is_some(),
+            Condition::IsLost => board.is_lost,
+        }
+    }
+}
+
+impl Task {
+    /// How far from the queen a soldier wanders when there is nothing to fight
+    const PATROL_RADIUS: i32 = 4;
+    /// How far an explorer goes looking for new places
+    const EXPLORATION_RADIUS: i32 = 12;
+
+    fn run(self, board: &mut Blackboard) -> Status {
+        let (pos, ctx, memory) = (board.pos, board.ctx, &board.memory);
+        let go_to = |goal: Pos| {
+            if pos == goal { Status::Success } else { Status::Running(Action::GoTo(goal)) }
+        };
+
+        let action = match self {
+            Task::FightBack => combat::fight_back(pos, ctx),
+            Task::Charge => {
+                let enemy = board.seen.enemies.first().copied().or(memory.enemy.map(|(p, _)| p));
+                enemy.map(Action::GoTo)
+            }
+            Task::GoToRally => {
+                return ctx.rally.map_or(Status::Failure, go_to);
+            }
+            Task::BringCargo => board.carrying.map(|cargo| bring(pos, cargo, ctx)),
+            Task::Tend => board.needs.and_then(|needs| needs.tend(pos, ctx)),
+            Task::Work => {
+                return board.job.map_or(Status::Failure, |job| work_on(pos, job));
+            }
+            Task::PickUpFood => food_around(pos, ctx.chunk_mngr).map(Action::PickUp),
+            Task::GoToFood => {
+                let food = board.seen.food.first().copied().or(memory.food.map(|(p, _)| p));
+                food.map(Action::GoTo)
+            }
+            Task::FollowFood => ctx.pheromones.gradient(pos, Pheromone::Food).map(Action::Walk),
+            Task::Retrace => memory.way_back().map(Action::GoTo),
+            Task::Wander => {
+                return wander(&mut board.rng);
+            }
+            Task::Explore => {
+                let rng = &mut board.rng;
+                Some(Action::GoTo(board.heading.unwrap_or_else(|| explore(pos, ctx, rng))))
+            }
+            Task::Patrol => {
+                let (x, y, _) = pos;
+                let (nx, ny, _) = ctx.nest;
+                if (x - nx).abs() > Self::PATROL_RADIUS || (y - ny).abs() > Self::PATROL_RADIUS {
+                    return Status::Running(Action::GoTo(ctx.nest));
+                }
+                return wander(&mut board.rng);
+            }
+            Task::StayHome => {
+                return go_to(ctx.nest);
+            }
+        };
+        action.map_or(Status::Failure, Status::Running)
+    }
+}
+
+/// Tree of a caste, parsed once
+pub fn tree(caste: Caste) -> &'static Node {
+    static TREES: OnceLock<Vec<(Caste, Node)>> = OnceLock::new();
+
+    let trees = TREES.get_or_init(|| {
+        [
+            (Caste::Worker, include_str!("../../assets/behaviors/worker.toml")),
+            (Caste::Explorer, include_str!("../../assets/behaviors/explorer.toml")),
+            (Caste::Soldier, include_str!("../../assets/behaviors/soldier.toml")),
+            (Caste::Nurse, include_str!("../../assets/behaviors/nurse.toml")),
+            (Caste::Drone, include_str!("../../assets/behaviors/alate.toml")),
+            (Caste::Gyne, include_str!("../../assets/behaviors/alate.toml")),
+        ]
+            .into_iter()
+            .map(|(caste, file)| (caste, parse(file).unwrap_or_else(|e| panic!("{:?} tree : {}", caste, e))))
+            .collect()
+    });
+    trees
+        .iter()
+        .find(|(c, _)| *c == caste)
+        .map(|(_, tree)| tree)
+        .expect("Every caste has a tree")
+}
+
+/// A tree file has a single `root` node
+pub fn parse(file: &str) -> Result<Node, toml::de::Error> {
+    #[derive(Deserialize)]
+    struct File {
+        root: Node,
+    }
+    toml::from_str::<File>(file).map(|file| file.root)
+}
+
+/// Food home, spoil to the mound, brood to the chamber
+fn bring(pos: Pos, cargo: Cargo, ctx: &Context) -> Action {
+    let goal = match cargo {
+        Cargo::Food(_) => ctx.storage,
+        Cargo::Spoil(_) => ctx.mound,
+        Cargo::Brood(_) => ctx.brood_chamber,
+    };
+    if (pos.0, pos.1) == (goal.0, goal.1) { Action::Drop } else { Action::GoTo(goal) }
+}
+
+/// Goes to the job, then does it
+fn work_on(pos: Pos, job: Job) -> Status {
+    // Food can be taken from the tile the ant stands on, not dug
+    let can_take = is_within_reach(pos, job.target);
+    let can_reach = can_take && pos != job.target;
+
+    let action = match job.kind {
+        JobKind::Dig if can_reach => Action::Dig(job.target),
+        JobKind::Build if can_reach => Action::Build(job.target),
+        JobKind::Forage | JobKind::Haul if can_take => Action::PickUp(job.target),
+        JobKind::Nurse if can_take => Action::Feed(job.target),
+        JobKind::MoveBrood if can_take => Action::Carry(job.target),
+        // Stand guard
+        JobKind::Guard if pos == job.from => {
+            return Status::Success;
+        }
+        _ => Action::GoTo(job.from),
+    };
+    Status::Running(action)
+}
+
+/// Edible tile the ant can reach without moving, if any
+fn food_around(pos: Pos, chunk_mngr: &ChunkManager) -> Option<Pos> {
+    let (x, y, z) = pos;
+    let around = [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)];
+
+    around
+        .iter()
+        .flat_map(|(dx, dy)| (z - 1..=z + 1).map(move |z| (x + dx, y + dy, z)))
+        .find(|p| chunk_mngr.tile_at(*p).is_some_and(|t| t.food_quantity() > 0))
+}
+
+fn wander(rng: &mut StdRng) -> Status {
+    match rng.gen() {
+        Direction::Up | Direction::Down => Status::Success,
+        direction => Status::Running(Action::Walk(direction)),
+    }
+}
+
+/// Somewhere to stand, or a wall and the next think() will pick another place
+fn explore(pos: Pos, ctx: &Context, rng: &mut StdRng) -> Pos {
+    let radius = Task::EXPLORATION_RADIUS;
+    let (x, y, z) = pos;
+    let goal = (x + rng.gen_range(-radius..=radius), y + rng.gen_range(-radius..=radius), z + 2);
+
+    apply_gravity(&goal, ctx.chunk_mngr)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{
+            behavior::{ self, Blackboard, Task },
+            brood::{ Brood, Caste },
+            jobs::{ Job, JobKind },
+            perception::{ Memory, Perception },
+            pheromone::PheromoneField,
+            sim_rng,
+            spatial::SpatialIndex,
+            Action,
+            Cargo,
+            ColonyMember,
+            Context,
+        },
+        chunk::{ tile::Tile, ChunkManager },
+    };
+
+    #[test]
+    fn trees_are_loaded() {
+        for caste in [Caste::Worker, Caste::Explorer, Caste::Soldier, Caste::Nurse, Caste::Drone, Caste::Gyne] {
+            behavior::tree(caste);
+        }
+        assert!(behavior::parse("root = { do = \"dance\" }").is_err());
+    }
+
+    #[test]
+    fn workers_build_before_bringing_spoil_back() {
+        let mngr = ChunkManager::flat_world();
+        let pheromones = PheromoneField::default();
+        let ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: (2, 2, 2),
+            storage: (2, 2, 2),
+            food: 10,
+            tick: 0,
+            seed: 0,
+            needed_caste: Caste::Worker,
+            mound: (0, 2, 2),
+            enemies: &SpatialIndex::default(),
+            rally: None,
+            brood_chamber: (2, 2, 2),
+        };
+        let build = Job {
+            id: 0,
+            kind: JobKind::Build,
+            target: (6, 2, 2),
+            from: (5, 2, 2),
+            priority: JobKind::Build.priority(),
+            reserved: None,
+        };
+        let mut board = Blackboard {
+            ctx: &ctx,
+            pos: (4, 2, 2),
+            carrying: Some(Cargo::Spoil(Tile::DIRT)),
+            job: Some(build),
+            needs: None,
+            heading: None,
+            is_lost: false,
+            seen: &Perception::default(),
+            memory: Memory::default(),
+            rng: sim_rng(0, 0, 0),
+        };
+        let worker = behavior::tree(Caste::Worker);
+        assert!(matches!(worker.decide(&mut board), (Some(Action::GoTo((5, 2, 2))), Some(Task::Work))));
+
+        board.job = None;
+        assert!(matches!(worker.decide(&mut board), (Some(Action::GoTo((0, 2, 2))), Some(Task::BringCargo))));
+
+        // Explorers stand guard
+        board.job = Some(Job { kind: JobKind::Guard, ..build });
+        board.pos = (5, 2, 2);
+        assert!(matches!(behavior::tree(Caste::Explorer).decide(&mut board), (None, Some(Task::Work))));
+    }
+
+    #[test]
+    fn workers_go_for_the_food_they_saw() {
+        let mngr = ChunkManager::flat_world();
+        let pheromones = PheromoneField::default();
+        let ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: (2, 2, 2),
+            storage: (2, 2, 2),
+            food: 10,
+            tick: 0,
+            seed: 0,
+            needed_caste: Caste::Worker,
+            mound: (0, 2, 2),
+            enemies: &SpatialIndex::default(),
+            rally: None,
+            brood_chamber: (2, 2, 2),
+        };
+        let memory = Memory {
+            food: Some(((12, 4, 2), 0)),
+            enemy: None,
+            route: [(6, 2, 2), (7, 2, 2)].into(),
+        };
+        let mut board = Blackboard {
+            ctx: &ctx,
+            pos: (8, 2, 2),
+            carrying: None,
+            job: None,
+            needs: None,
+            heading: None,
+            is_lost: false,
+            seen: &Perception::default(),
+            memory,
+            rng: sim_rng(0, 0, 0),
+        };
+        let worker = behavior::tree(Caste::Worker);
+        assert!(matches!(worker.decide(&mut board), (Some(Action::GoTo((12, 4, 2))), Some(Task::GoToFood))));
+
+        // What it sees beats what it remembers
+        let mut seen = Perception::default();
+        seen.food.push((9, 3, 2));
+        board.seen = &seen;
+        assert!(matches!(worker.decide(&mut board), (Some(Action::GoTo((9, 3, 2))), Some(Task::GoToFood))));
+
+        board.carrying = Some(Cargo::Food(1));
+        board.is_lost = true;
+        assert!(matches!(worker.decide(&mut board), (Some(Action::GoTo((6, 2, 2))), Some(Task::Retrace))));
+    }
+
+    #[test]
+    fn soldier_charges_enemies() {
+        let mngr = ChunkManager::flat_world();
+        let pheromones = PheromoneField::default();
+        let mut soldier = Caste::Soldier.hatch((2, 2, 2));
+        let mut ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: (2, 2, 2),
+            storage: (2, 2, 2),
+            food: 0,
+            tick: 0,
+            seed: 0,
+            needed_caste: Caste::Worker,
+            mound: (0, 2, 2),
+            enemies: &SpatialIndex::new([(7, 2, 2), (30, 2, 2)]),
+            rally: Some((10, 5, 2)),
+            brood_chamber: (2, 2, 2),
+        };
+        assert!(matches!(soldier.as_mut().think(&ctx), Some(Action::GoTo((7, 2, 2)))));
+
+        soldier.pos = (6, 2, 2);
+        assert!(matches!(soldier.as_mut().think(&ctx), Some(Action::Attack((7, 2, 2)))));
+
+        // Nobody around, off to the raid
+        let far_away = SpatialIndex::new([(30, 2, 2)]);
+        ctx.enemies = &far_away;
+        assert!(matches!(soldier.as_mut().think(&ctx), Some(Action::GoTo((10, 5, 2)))));
+    }
+
+    #[test]
+    fn nurse_moves_brood() {
+        let mngr = ChunkManager::flat_world();
+        let pheromones = PheromoneField::default();
+        let mut nurse = Caste::Nurse.hatch((2, 2, 2));
+        let ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: (2, 2, 2),
+            storage: (2, 2, 2),
+            food: 10,
+            tick: 0,
+            seed: 0,
+            needed_caste: Caste::Worker,
+            mound: (0, 2, 2),
+            enemies: &SpatialIndex::default(),
+            rally: None,
+            brood_chamber: (9, 4, 2),
+        };
+        // Nothing to do, stays with the queen
+        assert!(nurse.as_mut().think(&ctx).is_none());
+
+        let job = Job {
+            id: 0,
+            kind: JobKind::MoveBrood,
+            target: (5, 2, 2),
+            from: (5, 2, 2),
+            priority: JobKind::MoveBrood.priority(),
+            reserved: None,
+        };
+        nurse.job = Some(job);
+        assert!(matches!(nurse.as_mut().think(&ctx), Some(Action::GoTo((5, 2, 2)))));
+
+        nurse.pos = (4, 2, 2);
+        assert!(matches!(nurse.as_mut().think(&ctx), Some(Action::Carry((5, 2, 2)))));
+
+        nurse.cargo = Some(Cargo::Brood(Brood::egg((5, 2, 2), Caste::Worker)));
+        assert!(matches!(nurse.as_mut().think(&ctx), Some(Action::GoTo((9, 4, 2)))));
+        nurse.pos = (9, 4, 2);
+        assert!(matches!(nurse.as_mut().think(&ctx), Some(Action::Drop)));
+    }
+}
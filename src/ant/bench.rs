@@ -0,0 +1,85 @@
+//! Colony benchmarks, run them with
+//! `cargo test --release bench -- --ignored --nocapture`
+use std::time::{ Duration, Instant };
+
+use crate::{
+    ant::{ ai::Difficulty, brood::Caste, colony::{ Colony, Owner }, AntManager },
+    chunk::{ manager::LoadedChunk, tile::Tile, ChunkManager, WIDTH },
+};
+
+const ANTS: i32 = 10_000;
+const TICKS: u64 = 300;
+/// Ticks the first actions are spread over, as long as the longest chore
+const STAGGER: u64 = 30;
+/// A tick has to fit in a frame at 60 fps
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+/// Chunks on each side of the origin
+const WORLD_SIZE: i32 = 8;
+/// Ground level, deep enough for the nests
+const GROUND: i32 = 10;
+
+/// Flat dirt, with a patch of grass here and there
+fn world() -> ChunkManager {
+    let mut mngr = ChunkManager::empty();
+    for cx in -WORLD_SIZE..WORLD_SIZE {
+        for cy in -WORLD_SIZE..WORLD_SIZE {
+            mngr.loaded_chunks.insert((cx, cy), LoadedChunk::new((cx, cy)));
+        }
+    }
+    let size = (WIDTH as i32) * WORLD_SIZE;
+    for x in -size..size {
+        for y in -size..size {
+            mngr.set_tile((x, y, 0), Tile::BEDROCK);
+            for z in 1..GROUND {
+                mngr.set_tile((x, y, z), Tile::DIRT);
+            }
+            if x % 7 == 0 && y % 5 == 0 {
+                mngr.set_tile((x, y, GROUND), Tile::GRASS);
+            }
+        }
+    }
+    mngr
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn bench_ten_thousand_ants() {
+    let mngr = world();
+    let mut manager = AntManager::empty(0, Difficulty::Normal);
+
+    // Two colonies facing each other, so soldiers have someone to look for
+    for (name, nest, owner) in [("West", -30, Owner::Player), ("East", 30, Owner::Ai)] {
+        let mut colony = Colony::new(name, (nest, 0, GROUND));
+        colony.owner = owner;
+        colony.food = 1_000;
+        // As many of each caste as the colony wants
+        let castes: Vec<Caste> = colony.caste_targets
+            .iter()
+            .flat_map(|(caste, share)| vec![*caste; (share * 20.0).round() as usize])
+            .collect();
+        for i in 0..ANTS / 2 {
+            let pos = (nest + i % 41 - 20, i / 41 % 41 - 20, GROUND);
+            let mut ant = castes[(i as usize) % castes.len()].hatch(pos);
+            // Hatched over a while, not all thinking on the same tick
+            ant.next_action = (i as usize / castes.len()) as u64 % STAGGER;
+            colony.recruit(ant);
+        }
+        manager.found(colony);
+    }
+
+    let mut ticks = vec![];
+    for tick in 0..TICKS {
+        let start = Instant::now();
+        manager.tick(&mngr, tick);
+        ticks.push(start.elapsed());
+    }
+    let per_tick = ticks.iter().sum::<Duration>() / (TICKS as u32);
+    ticks.sort();
+    let (p99, worst) = (ticks[ticks.len() * 99 / 100], ticks[ticks.len() - 1]);
+
+    let ants: usize = manager.colonies.iter().map(|colony| colony.ants.len()).sum();
+    println!("{} ants, {:.2?} per tick, {:.2?} for 99% of them, {:.2?} at worst", ants, per_tick, p99, worst);
+    // A few slow ticks stutter even if the average fits
+    assert!(p99 < FRAME_BUDGET, "1% of the ticks take longer than {:?}", p99);
+}
+
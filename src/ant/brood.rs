@@ -0,0 +1,244 @@
+use sdl2::pixels::Color;
+
+use crate::{
+    ant::store::Ant,
+    renderer::Renderer,
+    time::TICKS_PER_MINUTE,
+};
+
+const EGG_TICKS: u64 = TICKS_PER_MINUTE * 2;
+const LARVA_TICKS: u64 = TICKS_PER_MINUTE * 4;
+const PUPA_TICKS: u64 = TICKS_PER_MINUTE * 3;
+
+/// A larva asks for food when it hasn't eaten for this long
+const LARVA_MEAL_TICKS: u64 = TICKS_PER_MINUTE;
+/// And dies if nobody comes
+const LARVA_STARVATION_TICKS: u64 = TICKS_PER_MINUTE * 3;
+/// Larvae fed less than this only make small workers
+const WELL_FED_MEALS: u32 = 3;
+/// Brood grows best at this temperature, in °C
+pub const IDEAL_TEMPERATURE: f32 = 22.0;
+/// And stops growing when it's further from it than that
+const TEMPERATURE_TOLERANCE: f32 = 4.0;
+
+/// What an adult ant is made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Caste {
+    /// Digs, builds and forages
+    Worker,
+    /// Maps the world for the colony
+    Explorer,
+    /// Big jaws, fights and guards
+    Soldier,
+    /// Takes care of the brood and the queen
+    Nurse,
+    /// Winged male, only lives for the nuptial flight
+    Drone,
+    /// Winged young queen, founds a colony after the nuptial flight
+    Gyne,
+}
+
+impl Caste {
+    pub fn hatch(self, pos: (i32, i32, i32)) -> Ant {
+        Ant::new(self, pos, self.lifespan())
+    }
+
+    /// In ticks
+    pub fn lifespan(self) -> u64 {
+        match self {
+            // About a game day
+            Caste::Worker => TICKS_PER_MINUTE * 60 * 24,
+            // Explorers take more risks, and fights wear soldiers out
+            Caste::Explorer | Caste::Soldier => TICKS_PER_MINUTE * 60 * 12,
+            // Nurses stay in the nest, they live longer
+            Caste::Nurse => TICKS_PER_MINUTE * 60 * 36,
+            // Laid in spring, they don't live long after the summer
+            Caste::Drone | Caste::Gyne => TICKS_PER_MINUTE * 60 * 24 * 4,
+        }
+    }
+
+    /// Winged ants, raised for the nuptial flight
+    pub fn is_alate(self) -> bool {
+        matches!(self, Caste::Drone | Caste::Gyne)
+    }
+
+    /// Whether it has the jaws to carry food, spoil and brood around
+    pub fn can_carry(self) -> bool {
+        matches!(self, Caste::Worker | Caste::Nurse)
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Caste::Worker => Color::RGB(255, 0, 0),
+            Caste::Explorer => Color::RGB(200, 0, 0),
+            Caste::Soldier => Color::RGB(120, 0, 60),
+            Caste::Nurse => Color::RGB(255, 120, 160),
+            Caste::Drone => Color::RGB(90, 60, 30),
+            Caste::Gyne => Color::RGB(250, 200, 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Egg,
+    Larva,
+    Pupa,
+}
+
+/// What happened to a brood during a tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Growth {
+    Growing,
+    Emerged(Caste),
+    Starved,
+}
+
+/// Eggs, larvae and pupae, kept in the nest until they become adults
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Brood {
+    pub pos: (i32, i32, i32),
+    pub stage: Stage,
+    /// Caste the queen laid it for, a starved larva may still end up a worker
+    pub caste: Caste,
+    /// Ticks spent in the current stage
+    pub age: u64,
+    /// Ticks since a nurse last fed it
+    pub hunger: u64,
+    pub meals: u32,
+}
+
+impl Brood {
+    pub fn egg(pos: (i32, i32, i32), caste: Caste) -> Self {
+        Self { pos, stage: Stage::Egg, caste, age: 0, hunger: 0, meals: 0 }
+    }
+
+    /// Brood too cold or too hot doesn't grow, but larvae still get hungry
+    pub fn tick(&mut self, temperature: f32) -> Growth {
+        if Self::is_comfortable(temperature) {
+            self.age += 1;
+        }
+
+        match self.stage {
+            Stage::Egg if self.age >= EGG_TICKS => {
+                self.stage = Stage::Larva;
+                self.age = 0;
+            }
+            Stage::Larva => {
+                self.hunger += 1;
+                if self.hunger >= LARVA_STARVATION_TICKS {
+                    return Growth::Starved;
+                }
+                if self.age >= LARVA_TICKS {
+                    // Not enough food to grow into anything else
+                    if self.meals < WELL_FED_MEALS {
+                        self.caste = Caste::Worker;
+                    }
+                    self.stage = Stage::Pupa;
+                    self.age = 0;
+                }
+            }
+            Stage::Pupa if self.age >= PUPA_TICKS => {
+                return Growth::Emerged(self.caste);
+            }
+            _ => {}
+        }
+        Growth::Growing
+    }
+
+    pub fn is_comfortable(temperature: f32) -> bool {
+        (temperature - IDEAL_TEMPERATURE).abs() <= TEMPERATURE_TOLERANCE
+    }
+
+    /// Only larvae eat
+    pub fn is_hungry(&self) -> bool {
+        self.stage == Stage::Larva && self.hunger >= LARVA_MEAL_TICKS
+    }
+
+    pub fn feed(&mut self) {
+        self.hunger = 0;
+        self.meals += 1;
+    }
+
+    pub fn render(&self, renderer: &mut Renderer) {
+        let (x, y, z) = self.pos;
+
+        if z > renderer.camera.2 {
+            return;
+        }
+        let color = match self.stage {
+            Stage::Egg => Color::RGB(250, 250, 240),
+            Stage::Larva => Color::RGB(240, 220, 180),
+            Stage::Pupa => Color::RGB(200, 170, 120),
+        };
+        let (x, y) = renderer.tile_to_screen_coords((x, y));
+        renderer.draw_tile((x, y), color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::brood::{
+        Brood,
+        Caste,
+        Growth,
+        Stage,
+        EGG_TICKS,
+        IDEAL_TEMPERATURE,
+        LARVA_MEAL_TICKS,
+        LARVA_TICKS,
+    };
+
+    /// Ticks until something else than growing happens
+    fn grow(brood: &mut Brood, feed: bool) -> Growth {
+        loop {
+            if feed && brood.is_hungry() {
+                brood.feed();
+            }
+            match brood.tick(IDEAL_TEMPERATURE) {
+                Growth::Growing => {}
+                growth => {
+                    return growth;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lifecycle() {
+        let mut brood = Brood::egg((0, 0, 0), Caste::Explorer);
+        for _ in 0..EGG_TICKS {
+            brood.tick(IDEAL_TEMPERATURE);
+        }
+        assert_eq!(brood.stage, Stage::Larva);
+        assert!(!brood.is_hungry(), "Eggs don't eat");
+
+        for _ in 0..LARVA_MEAL_TICKS {
+            brood.tick(IDEAL_TEMPERATURE);
+        }
+        assert!(brood.is_hungry());
+
+        // Fed larvae become what they were laid for
+        assert_eq!(grow(&mut brood, true), Growth::Emerged(Caste::Explorer));
+        assert!(brood.meals >= 3);
+
+        // Nobody feeds this one
+        let mut brood = Brood::egg((0, 0, 0), Caste::Explorer);
+        assert_eq!(grow(&mut brood, false), Growth::Starved);
+
+        // Fed just enough to survive
+        let mut brood = Brood::egg((0, 0, 0), Caste::Explorer);
+        for _ in 0..EGG_TICKS + LARVA_TICKS / 2 {
+            brood.tick(IDEAL_TEMPERATURE);
+        }
+        brood.feed();
+        assert_eq!(grow(&mut brood, false), Growth::Emerged(Caste::Worker));
+
+        // Cold eggs never hatch
+        let mut brood = Brood::egg((0, 0, 0), Caste::Explorer);
+        for _ in 0..EGG_TICKS * 2 {
+            brood.tick(IDEAL_TEMPERATURE - 10.0);
+        }
+        assert_eq!(brood.stage, Stage::Egg);
+    }
+}
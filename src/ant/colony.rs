@@ -1,119 +1,1180 @@
-use std::time::{ Duration, Instant };
+use sdl2::pixels::Color;
 
 use crate::{
     ant::{
-        explorer::Explorer,
         apply_gravity,
-        queen::{ Egg, Queen },
+        pathfinding::Pathfinder,
+        pheromone::PheromoneField,
+        brood::{ Brood, Caste, Growth, IDEAL_TEMPERATURE },
+        combat::Stats,
+        designation::{ Area, Designation, Designations },
+        direction::Direction,
+        explored::ExploredMap,
+        is_within_reach,
+        jobs::{ AntId, Job, JobBoard, JobKind },
+        nest::NestPlan,
+        perception::{ self, Senses },
+        queen::Queen,
+        spatial::SpatialIndex,
+        store::{ Ant, Ants },
         Action,
+        Cargo,
         ColonyMember,
+        Context,
     },
-    chunk::{ ChunkManager, SEA_LEVEL },
+    chunk::{ tile::{ Tile, TileFlag }, ChunkManager, HEIGHT, SEA_LEVEL },
+    time::{ self, Season },
 };
 
+/// Stable id of a colony, given when it is founded
+pub type ColonyId = u32;
+
+/// Who gives the orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner {
+    Player,
+    Ai,
+    /// Nobody, ants just live their lives
+    Wildlife,
+}
+
 pub struct Colony {
-    pub name: &'static str,
+    pub id: ColonyId,
+    pub name: String,
+    /// Ants are outlined with it
+    pub color: Color,
+    pub owner: Owner,
     pub queen: Queen,
-    pub ants: Vec<Box<dyn ColonyMember>>,
+    pub ants: Ants,
+    pub pathfinder: Pathfinder,
+    pub pheromones: PheromoneField,
+    /// Food stockpile
+    pub food: u32,
+    /// Designated storage chamber, the queen's chamber if there is none
+    pub storage: Option<(i32, i32, i32)>,
+    /// Eggs, larvae and pupae
+    pub brood: Vec<Brood>,
+    /// Tunnels to dig, planned once the queen has landed
+    pub nest_plan: Option<NestPlan>,
+    /// What the player wants done
+    pub designations: Designations,
+    pub jobs: JobBoard,
+    /// Given to the next ant joining the colony
+    next_ant_id: AntId,
+    /// Dead ants waiting to be brought back to the stockpile
+    corpses: Vec<(i32, i32, i32)>,
+    /// Where soldiers gather for a raid, if any
+    pub rally: Option<(i32, i32, i32)>,
+    /// Bites given this tick, with their damage, see AntManager::resolve_combat
+    pub attacks: Vec<((i32, i32, i32), u32)>,
+    /// Share of the population each caste should make
+    pub caste_targets: [(Caste, f32); 4],
+    /// What the colony knows of the world, mapped by its explorers
+    pub explored: ExploredMap,
+    /// Share of the world seed, every ant rolls its dice from it
+    pub seed: u64,
 }
 impl Colony {
     const TEST_NAME: &'static str = "Bel-o-Kan";
     const AI_NAME: &'static str = "Com-p-uter";
+    const WILDLIFE_COLOR: Color = Color::RGB(120, 120, 120);
+    /// Pheromone left on each step
+    const TRAIL_STRENGTH: f32 = 5.0;
+    /// Food a single ant can carry
+    pub const CARRY_CAPACITY: u8 = 5;
+    /// Digging power of a worker, see ChunkManager::dig
+    const DIG_STRENGTH: u8 = 50;
+    /// Enough for the queen's first eggs
+    const STARTING_FOOD: u32 = 20;
+    /// Dead ants become food for the others
+    const LEAVE_CORPSES: bool = true;
+    /// Ants needed before raising alates
+    const MATURE_POPULATION: usize = 20;
+    /// Gynes and drones raised each spring
+    const ALATES_PER_YEAR: usize = 4;
+    /// What a young queen lives on until her first workers
+    const FOUNDING_FOOD: u32 = 12;
+    /// Known around the queen from the start
+    const NEST_SIGHT: i32 = 6;
+
+    /// Default share of the population each caste should make
+    const CASTE_TARGETS: [(Caste, f32); 4] = [
+        (Caste::Worker, 0.55),
+        (Caste::Nurse, 0.2),
+        (Caste::Explorer, 0.15),
+        (Caste::Soldier, 0.1),
+    ];
 
-    pub const PLAYER: usize = 0;
-    pub const AI: usize = 1;
+    /// `enemies` are the ants of every other colony
+    pub fn tick(&mut self, chunk_mngr: &ChunkManager, tick: u64, enemies: &SpatialIndex) {
+        self.pathfinder.start_tick();
+        self.post_jobs(chunk_mngr);
+        let chamber = self.brood_chamber(chunk_mngr);
+        self.jobs.expire(tick);
+
+        let ctx = Context {
+            chunk_mngr,
+            pheromones: &self.pheromones,
+            nest: self.queen.pos,
+            storage: self.storage(),
+            food: self.food,
+            tick,
+            seed: self.seed,
+            needed_caste: self.next_egg(tick),
+            mound: self.mound(chunk_mngr),
+            enemies,
+            rally: self.rally,
+            brood_chamber: chamber,
+        };
+        // Pheromones are left once everybody has thought
+        let mut trails = vec![];
+        let mut senses = Senses::default();
 
-    pub fn tick(&mut self, chunk_mngr: &ChunkManager) {
         //////////  QUEEN  /////////
+        self.queen.needs.tick();
+        self.queen.claustral = !self.ants.castes().contains(&Caste::Nurse);
 
-        if Instant::now().duration_since(self.queen.last_action()) > Duration::from_millis(1000) {
-            if let Some(action) = self.queen.think() {
+        if tick >= self.queen.next_action() {
+            if let Some(action) = self.queen.think(&ctx) {
+                self.queen.set_next_action(tick + action.duration());
                 match action {
-                    Action::Walk(_) => {
+                    Action::Walk(_) | Action::GoTo(_) => {
                         panic!("Why would the queen go anywhere ?");
                     }
-                    Action::Breed(mut newborns) => {
-                        self.ants.append(&mut newborns);
-                        self.queen.reset_last_action();
+                    Action::PickUp(_) |
+                    Action::Carry(_) |
+                    Action::Build(_) |
+                    Action::Drop |
+                    Action::Rest |
+                    Action::Feed(_) => {
+                        panic!("The queen doesn't carry anything");
+                    }
+                    Action::Eat => {
+                        self.food -= self.queen.needs.eat(self.food);
+                    }
+                    Action::Attack(pos) => {
+                        self.attacks.push((pos, self.queen.stats.attack));
+                    }
+                    Action::Dig(pos) => {
+                        Self::dig_founding_chamber(&mut self.queen, pos, chunk_mngr);
+                        self.pathfinder.invalidate();
+                    }
+                    Action::Breed(mut eggs) => {
+                        let cost = (eggs.len() as u32) * Queen::EGG_COST;
+                        self.food = self.food.saturating_sub(cost);
+                        self.brood.append(&mut eggs);
                     }
                 }
+            } else {
+                self.queen.set_next_action(tick + Action::IDLE);
             }
             // Gravity check !
             let new_pos = apply_gravity(&self.queen.pos, chunk_mngr);
             self.queen.set_pos(new_pos);
         }
 
+        if self.queen.claustral {
+            self.food -= Self::raise_brood_alone(&mut self.brood, self.queen.pos, self.food);
+        }
+
+        // Plans are made once the queen has landed, and dug her chamber
+        let has_landed = apply_gravity(&self.queen.pos, chunk_mngr) == self.queen.pos;
+        if self.nest_plan.is_none() && self.queen.founding.is_none() && has_landed {
+            self.nest_plan = Some(NestPlan::around(self.queen.pos));
+        }
+
         //////////  ANTS  /////////
-        for ant in self.ants.iter_mut() {
-            if Instant::now().duration_since(ant.last_action()) > Duration::from_millis(1000) {
-                println!(
-                    "ant took {:.2?} to do something",
-                    Instant::now().duration_since(ant.last_action())
-                );
-                if let Some(action) = ant.think() {
+        for i in 0..self.ants.len() {
+            let mut ant = self.ants.get_mut(i);
+            if let Some(needs) = ant.needs_mut() {
+                needs.tick();
+            }
+            if tick >= ant.next_action() {
+                let from = ant.pos();
+                Self::assign_job(&mut self.jobs, &mut ant, tick);
+
+                let caste = ant.caste().expect("Ants all have a caste");
+                let seen = senses.sense(ant.pos(), perception::sight(caste), &ctx);
+                // Explorers map what they see for the colony
+                if caste == Caste::Explorer {
+                    self.explored.reveal_seen(seen);
+                }
+
+                if let Some(action) = ant.think_about(seen, &ctx) {
+                    ant.set_next_action(tick + action.duration());
                     match action {
                         Action::Walk(direction) => {
-                            ant.walk(&chunk_mngr, direction);
-                            ant.reset_last_action();
+                            ant.walk(chunk_mngr, self.pathfinder.keep_out(), direction);
+                        }
+                        Action::GoTo(goal) => {
+                            ant.follow_path(goal, chunk_mngr, &mut self.pathfinder);
+                        }
+                        Action::PickUp(pos) => {
+                            Self::pick_up(&mut ant, pos, chunk_mngr);
+                            senses.forget(pos);
+                            // Others will come for the rest
+                            if chunk_mngr.tile_at(pos).is_some_and(|t| t.food_quantity() > 0) {
+                                let priority = JobKind::Forage.priority();
+                                self.jobs.post(JobKind::Forage, pos, ant.pos(), priority);
+                            }
+                        }
+                        Action::Carry(pos) => {
+                            Self::carry(&mut ant, pos, &mut self.brood);
+                        }
+                        Action::Dig(pos) => {
+                            if Self::dig(&mut ant, pos, chunk_mngr) {
+                                self.pathfinder.invalidate();
+                            }
+                            // Even a tile that holds is damaged
+                            senses.forget(pos);
+                        }
+                        Action::Build(pos) => {
+                            if Self::build(&mut ant, pos, chunk_mngr) {
+                                self.pathfinder.invalidate();
+                                senses.forget(pos);
+                            }
+                        }
+                        Action::Drop => {
+                            if Self::dump(&mut ant, ctx.mound, chunk_mngr) {
+                                self.pathfinder.invalidate();
+                                senses.invalidate();
+                            }
+                            self.food += Self::drop(&mut ant, ctx.storage);
+                            self.brood.extend(Self::put_down(&mut ant));
+                        }
+                        Action::Eat => {
+                            self.food -= Self::eat(&mut ant, ctx.storage, self.food);
+                        }
+                        Action::Rest => {
+                            if let Some(needs) = ant.needs_mut() {
+                                needs.rest();
+                            }
+                        }
+                        Action::Feed(pos) if pos == self.queen.pos => {
+                            if is_within_reach(ant.pos(), pos) {
+                                self.food -= self.queen.needs.eat(self.food);
+                            }
+                        }
+                        Action::Feed(pos) => {
+                            if Self::feed(&mut self.brood, ant.pos(), pos, self.food) {
+                                self.food -= 1;
+                            }
+                        }
+                        Action::Attack(pos) => {
+                            if is_within_reach(ant.pos(), pos) {
+                                self.attacks.push((pos, ant.stats_mut().attack));
+                            }
                         }
                         Action::Breed(_) => {
                             panic!("Only the queen must breed ! ");
                         }
                     }
+
+                    // Only where it went, not where it stood
+                    let moved = ant.pos() != from;
+                    if let Some(pheromone) = ant.trail().filter(|_| moved) {
+                        trails.push((ant.pos(), pheromone));
+                    }
+                } else {
+                    ant.set_next_action(tick + Action::IDLE);
                 }
+                Self::check_job(&mut self.jobs, &mut ant, chunk_mngr, &self.brood, &self.queen);
             }
             // Gravity check !
             let new_pos = apply_gravity(&ant.pos(), chunk_mngr);
             ant.set_pos(new_pos);
         }
+
+        self.bury_the_dead(chunk_mngr);
+        self.grow_brood(chunk_mngr, chamber);
+        self.designations.prune(chunk_mngr);
+
+        for (pos, pheromone) in trails {
+            self.pheromones.deposit(pos, pheromone, Self::TRAIL_STRENGTH);
+        }
+        self.pheromones.tick(tick);
+    }
+
+    pub fn storage(&self) -> (i32, i32, i32) {
+        self.storage.unwrap_or(self.queen.pos)
+    }
+
+    /// Harvests the tile at `pos` if the ant is next to it and has free hands
+    fn pick_up(ant: &mut dyn ColonyMember, pos: (i32, i32, i32), chunk_mngr: &ChunkManager) {
+        if !is_within_reach(ant.pos(), pos) {
+            return;
+        }
+
+        if let Some(cargo @ None) = ant.cargo_mut() {
+            let taken = chunk_mngr.harvest(pos, Self::CARRY_CAPACITY);
+            if taken > 0 {
+                *cargo = Some(Cargo::Food(taken));
+            }
+        }
+    }
+
+    /// Takes the brood at `pos` if the ant is next to it and has free hands
+    fn carry(ant: &mut dyn ColonyMember, pos: (i32, i32, i32), brood: &mut Vec<Brood>) {
+        if !is_within_reach(ant.pos(), pos) {
+            return;
+        }
+        let Some(cargo @ None) = ant.cargo_mut() else {
+            return;
+        };
+        if let Some(i) = brood.iter().position(|b| b.pos == pos) {
+            *cargo = Some(Cargo::Brood(brood.swap_remove(i)));
+        }
+    }
+
+    /// Puts the carried brood down where the ant stands
+    fn put_down(ant: &mut dyn ColonyMember) -> Option<Brood> {
+        let pos = ant.pos();
+        let cargo = ant.cargo_mut()?;
+        let Some(Cargo::Brood(mut brood)) = *cargo else {
+            return None;
+        };
+        *cargo = None;
+        brood.pos = pos;
+        Some(brood)
+    }
+
+    /// Food brought back to the storage goes to the stockpile
+    /// Returns how much food was stored
+    fn drop(ant: &mut dyn ColonyMember, storage: (i32, i32, i32)) -> u32 {
+        let pos = ant.pos();
+        if (pos.0, pos.1) != (storage.0, storage.1) {
+            return 0;
+        }
+
+        let Some(cargo) = ant.cargo_mut() else {
+            return 0;
+        };
+        match *cargo {
+            Some(Cargo::Food(amount)) => {
+                *cargo = None;
+                amount as u32
+            }
+            _ => 0,
+        }
+    }
+
+    /// Hits the tile at `pos` if the ant is next to it and has free hands
+    /// Returns whether the tile broke
+    fn dig(ant: &mut dyn ColonyMember, pos: (i32, i32, i32), chunk_mngr: &ChunkManager) -> bool {
+        if !is_within_reach(ant.pos(), pos) {
+            return false;
+        }
+
+        match ant.cargo_mut() {
+            Some(cargo @ None) => {
+                let spoil = chunk_mngr.dig(pos, Self::DIG_STRENGTH);
+                *cargo = spoil.map(Cargo::Spoil);
+                spoil.is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Fills the tile at `pos` with the carried spoil if the ant is next to it
+    /// Returns whether the tile was built
+    fn build(ant: &mut dyn ColonyMember, pos: (i32, i32, i32), chunk_mngr: &ChunkManager) -> bool {
+        if !is_within_reach(ant.pos(), pos) || pos == ant.pos() {
+            return false;
+        }
+        if chunk_mngr.tile_at(pos) != Some(Tile::AIR) {
+            return false;
+        }
+        let Some(cargo) = ant.cargo_mut() else {
+            return false;
+        };
+        let Some(Cargo::Spoil(tile)) = *cargo else {
+            return false;
+        };
+
+        *cargo = None;
+        chunk_mngr.set_tile(pos, tile);
+        true
+    }
+
+    /// Player designation, see Designations
+    pub fn designate(&mut self, area: Area, kind: Designation, chunk_mngr: &ChunkManager) {
+        self.designations.designate(area, kind, chunk_mngr);
+        if kind == Designation::KeepOut {
+            self.pathfinder.set_keep_out(self.designations.keep_out());
+        }
+    }
+
+    pub fn cancel_designation(&mut self, pos: (i32, i32, i32)) {
+        if self.designations.cancel_at(pos) {
+            self.pathfinder.set_keep_out(self.designations.keep_out());
+        }
+        // The nest plan's are posted again next tick
+        let designations = &self.designations;
+        self.jobs.jobs.retain(|job| {
+            !matches!(job.kind, JobKind::Dig | JobKind::Build) ||
+                designations.jobs.iter().any(|j| j.pos == job.target)
+        });
+    }
+
+    /// Spoil brought to the mound is piled on its lowest side, so it doesn't become a tower
+    /// Returns whether the world changed
+    fn dump(ant: &mut dyn ColonyMember, mound: (i32, i32, i32), chunk_mngr: &ChunkManager) -> bool {
+        let pos = ant.pos();
+        if (pos.0, pos.1) != (mound.0, mound.1) {
+            return false;
+        }
+        let Some(cargo) = ant.cargo_mut() else {
+            return false;
+        };
+        let Some(Cargo::Spoil(tile)) = *cargo else {
+            return false;
+        };
+        *cargo = None;
+
+        let spot = [Direction::North, Direction::East, Direction::South, Direction::West]
+            .iter()
+            .map(|d| apply_gravity(&Direction::Up.add_to(&d.add_to(&pos)), chunk_mngr))
+            .filter(|p| p.2 <= pos.2 && chunk_mngr.tile_at(*p) == Some(Tile::AIR))
+            .min_by_key(|p| p.2);
+
+        match spot {
+            Some(p) => chunk_mngr.set_tile(p, tile),
+            // Climbs on top of it
+            None => {
+                chunk_mngr.set_tile(pos, tile);
+                ant.set_pos(Direction::Up.add_to(&pos));
+            }
+        }
+        true
+    }
+
+    /// Where ants stand on top of the spoil mound
+    fn mound(&self, chunk_mngr: &ChunkManager) -> (i32, i32, i32) {
+        let (x, y, z) = NestPlan::mound(self.queen.pos);
+        let top = (z..(HEIGHT as i32) - 1)
+            .rev()
+            .find(|z| {
+                chunk_mngr
+                    .tile_at((x, y, *z))
+                    .is_some_and(|tile| !tile.properties.contains(TileFlag::TRAVERSABLE))
+            });
+
+        top.map_or((x, y, z), |top| (x, y, top + 1))
+    }
+
+    /// Eats from the stockpile if the ant is at the storage
+    /// Returns how much food was eaten
+    fn eat(ant: &mut dyn ColonyMember, storage: (i32, i32, i32), stock: u32) -> u32 {
+        let pos = ant.pos();
+        if (pos.0, pos.1) != (storage.0, storage.1) {
+            return 0;
+        }
+
+        ant.needs_mut().map_or(0, |needs| needs.eat(stock))
+    }
+
+    /// Removes dead ants, leaving their corpse where there is room for it
+    fn bury_the_dead(&mut self, chunk_mngr: &ChunkManager) {
+        let (jobs, corpses, brood) = (&mut self.jobs, &mut self.corpses, &mut self.brood);
+
+        self.ants.retain(|mut ant| {
+            let is_dead = ant.stats_mut().is_dead() ||
+                ant.needs_mut().is_some_and(|needs| needs.cause_of_death().is_some());
+            if !is_dead {
+                return true;
+            }
+            // Somebody else will do it
+            jobs.release(ant.id());
+            brood.extend(Self::put_down(&mut ant));
+
+            if Self::LEAVE_CORPSES && chunk_mngr.tile_at(ant.pos()) == Some(Tile::AIR) {
+                chunk_mngr.set_tile(ant.pos(), Tile::DEAD_INSECT);
+                corpses.push(ant.pos());
+            }
+            false
+        });
+    }
+
+    /// Where the queen and every ant stand
+    pub fn members(&self) -> Vec<(i32, i32, i32)> {
+        let queen = (!self.has_fallen()).then_some(self.queen.pos);
+        queen.into_iter().chain(self.ants.positions().iter().copied()).collect()
+    }
+
+    /// Stats of whoever is standing at `pos`, ants shield the queen
+    pub fn member_at(&mut self, pos: (i32, i32, i32)) -> Option<&mut Stats> {
+        if let Some(i) = self.ants.positions().iter().position(|p| *p == pos) {
+            return Some(&mut self.ants.stats_mut()[i]);
+        }
+        (self.queen.pos == pos && !self.has_fallen()).then_some(&mut self.queen.stats)
+    }
+
+    /// The colony is lost with its queen
+    pub fn has_fallen(&self) -> bool {
+        self.queen.stats.is_dead()
+    }
+
+    /// Sends the soldiers to `pos`
+    pub fn raid(&mut self, pos: (i32, i32, i32), chunk_mngr: &ChunkManager) {
+        self.rally = Some(apply_gravity(&pos, chunk_mngr));
+    }
+
+    /// Soldiers go back to guarding the queen
+    pub fn call_back(&mut self) {
+        self.rally = None;
+    }
+
+    /// New member, with its own id
+    pub fn recruit(&mut self, mut ant: Ant) {
+        ant.id = self.next_ant_id;
+        self.next_ant_id += 1;
+        self.ants.push(ant);
+    }
+
+    /// Puts everything that needs doing on the board, and forgets what's been done
+    fn post_jobs(&mut self, chunk_mngr: &ChunkManager) {
+        let (brood, queen) = (&self.brood, &self.queen);
+        self.jobs.jobs.retain(|job| !Self::is_done(job, chunk_mngr, brood, queen));
+
+        // The player comes first
+        for job in self.designations.jobs.iter().filter(|job| !job.is_done(chunk_mngr)) {
+            let kind = match job.kind {
+                Designation::Dig => JobKind::Dig,
+                Designation::Build => JobKind::Build,
+                Designation::KeepOut => {
+                    continue;
+                }
+            };
+            if let Some(dig) = job.as_dig(chunk_mngr) {
+                self.jobs.post(kind, dig.tile, dig.from, kind.priority() + 1);
+            }
+        }
+
+        if let Some(plan) = &self.nest_plan {
+            for dig in plan.available(chunk_mngr) {
+                self.jobs.post(JobKind::Dig, dig.tile, dig.from, JobKind::Dig.priority());
+            }
+        }
+
+        for larva in self.brood.iter().filter(|brood| brood.is_hungry()) {
+            self.jobs.post(JobKind::Nurse, larva.pos, larva.pos, JobKind::Nurse.priority());
+        }
+        // The queen eats first
+        if self.queen.needs.is_hungry() && self.food > 0 {
+            let queen = self.queen.pos;
+            self.jobs.post(JobKind::Nurse, queen, queen, JobKind::Nurse.priority() + 1);
+        }
+
+        self.corpses.retain(|p| chunk_mngr.tile_at(*p).is_some_and(|t| t.food_quantity() > 0));
+        for corpse in self.corpses.iter() {
+            self.jobs.post(JobKind::Haul, *corpse, *corpse, JobKind::Haul.priority());
+        }
+    }
+
+    /// Whether there is nothing left to do, whoever did it
+    fn is_done(job: &Job, chunk_mngr: &ChunkManager, brood: &[Brood], queen: &Queen) -> bool {
+        let Some(tile) = chunk_mngr.tile_at(job.target) else {
+            return true;
+        };
+
+        match job.kind {
+            JobKind::Dig => tile.properties.contains(TileFlag::TRAVERSABLE) || tile.hardness() == 0,
+            JobKind::Build => !tile.properties.contains(TileFlag::TRAVERSABLE),
+            JobKind::Forage | JobKind::Haul => tile.food_quantity() == 0,
+            JobKind::Nurse if job.target == queen.pos => !queen.needs.is_hungry(),
+            JobKind::Nurse => !brood.iter().any(|b| b.pos == job.target && b.is_hungry()),
+            JobKind::MoveBrood => !brood.iter().any(|b| b.pos == job.target),
+            // Guards stay until the job times out
+            JobKind::Guard => false,
+        }
+    }
+
+    /// Gives a job to an ant that has none, what it can do depends on what it carries
+    fn assign_job(jobs: &mut JobBoard, ant: &mut dyn ColonyMember, tick: u64) {
+        let (id, pos) = (ant.id(), ant.pos());
+        let Some(caste) = ant.caste() else {
+            return;
+        };
+        let cargo = ant.cargo_mut().and_then(|cargo| *cargo);
+        let Some(slot) = ant.job_mut() else {
+            return;
+        };
+
+        // Its reservation timed out, or the job was done by somebody else
+        if slot.is_some_and(|job| jobs.reserved_by(id).is_none_or(|j| j.id != job.id)) {
+            *slot = None;
+        }
+        if slot.is_none() {
+            *slot = jobs.take(id, caste, pos, tick, |job| {
+                match cargo {
+                    Some(Cargo::Spoil(_)) => job.kind == JobKind::Build,
+                    Some(Cargo::Food(_) | Cargo::Brood(_)) => false,
+                    None => job.kind != JobKind::Build,
+                }
+            });
+        }
+    }
+
+    /// Forgets the ant's job once it's done
+    fn check_job(
+        jobs: &mut JobBoard,
+        ant: &mut dyn ColonyMember,
+        chunk_mngr: &ChunkManager,
+        brood: &[Brood],
+        queen: &Queen
+    ) {
+        let cargo = ant.cargo_mut().and_then(|cargo| *cargo);
+        let Some(slot) = ant.job_mut() else {
+            return;
+        };
+        let Some(job) = *slot else {
+            return;
+        };
+
+        let picked_up = match job.kind {
+            JobKind::Forage | JobKind::Haul => matches!(cargo, Some(Cargo::Food(_))),
+            JobKind::MoveBrood => matches!(cargo, Some(Cargo::Brood(_))),
+            _ => false,
+        };
+        if picked_up || Self::is_done(&job, chunk_mngr, brood, queen) {
+            jobs.complete(job.id);
+            *slot = None;
+        }
+    }
+
+    /// Gives one food from the stockpile to the hungry larva at `pos`, if the nurse is next to it
+    /// Returns whether it was fed
+    fn feed(brood: &mut [Brood], nurse: (i32, i32, i32), pos: (i32, i32, i32), stock: u32) -> bool {
+        if stock == 0 || nurse.0.abs_diff(pos.0) + nurse.1.abs_diff(pos.1) > 1 {
+            return false;
+        }
+
+        match brood.iter_mut().find(|brood| brood.pos == pos && brood.is_hungry()) {
+            Some(larva) => {
+                larva.feed();
+                true
+            }
+            None => false,
+        }
     }
+
+    /// Dug out tile of the nest, or the queen's, where the temperature suits the brood best
+    pub fn brood_chamber(&self, chunk_mngr: &ChunkManager) -> (i32, i32, i32) {
+        let dug = self.nest_plan
+            .iter()
+            .flat_map(|plan| plan.digs.iter().map(|dig| dig.tile))
+            .filter(|p| {
+                chunk_mngr.tile_at(*p).is_some_and(|tile| tile.properties.contains(TileFlag::TRAVERSABLE)) &&
+                    apply_gravity(p, chunk_mngr) == *p
+            });
+
+        std::iter::once(self.queen.pos)
+            .chain(dug)
+            .map(|p| (p, (chunk_mngr.temperature_at(p) - IDEAL_TEMPERATURE).abs()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(self.queen.pos, |(p, _)| p)
+    }
+
+    /// Young queens dig until they reach something too hard
+    fn dig_founding_chamber(queen: &mut Queen, pos: (i32, i32, i32), chunk_mngr: &ChunkManager) {
+        let Some(left) = queen.founding else {
+            return;
+        };
+        if chunk_mngr.tile_at(pos).is_none_or(|tile| tile.hardness() == 0) {
+            queen.founding = None;
+            return;
+        }
+
+        if chunk_mngr.dig(pos, Queen::DIG_STRENGTH).is_some() {
+            queen.founding = (left > 1).then_some(left - 1);
+        }
+    }
+
+    /// Without nurses, the queen feeds the larvae next to her, returns the food used
+    fn raise_brood_alone(brood: &mut [Brood], queen: (i32, i32, i32), food: u32) -> u32 {
+        let hungry: Vec<(i32, i32, i32)> = brood
+            .iter()
+            .filter(|brood| brood.is_hungry())
+            .map(|brood| brood.pos)
+            .collect();
+        let mut used = 0;
+
+        for pos in hungry {
+            if Self::feed(brood, queen, pos, food - used) {
+                used += 1;
+            }
+        }
+        used
+    }
+
+    /// Alates in spring once the colony is big enough, by caste targets otherwise
+    fn next_egg(&self, tick: u64) -> Caste {
+        let alates: Vec<Caste> = self.ants
+            .castes()
+            .iter()
+            .copied()
+            .chain(self.brood.iter().map(|brood| brood.caste))
+            .filter(|caste| caste.is_alate())
+            .collect();
+        let is_mature = self.ants.len() >= Self::MATURE_POPULATION;
+
+        if time::season(tick) == Season::Spring && is_mature && alates.len() < Self::ALATES_PER_YEAR {
+            let gynes = alates.iter().filter(|caste| **caste == Caste::Gyne).count();
+            return if gynes * 2 <= alates.len() { Caste::Gyne } else { Caste::Drone };
+        }
+        self.needed_caste()
+    }
+
+    /// Alates leave for the nuptial flight, see AntManager::nuptial_flight
+    pub fn release_alates(&mut self) -> Vec<(Caste, (i32, i32, i32))> {
+        let mut alates = vec![];
+
+        self.ants.retain(|ant| {
+            match ant.caste() {
+                Some(caste) if caste.is_alate() => {
+                    alates.push((caste, ant.pos()));
+                    false
+                }
+                _ => true,
+            }
+        });
+        alates
+    }
+
+    /// Caste the furthest below its target share, counting the brood
+    pub fn needed_caste(&self) -> Caste {
+        let castes: Vec<Caste> = self.ants
+            .castes()
+            .iter()
+            .copied()
+            .chain(self.brood.iter().map(|brood| brood.caste))
+            .filter(|caste| !caste.is_alate())
+            .collect();
+        let total = castes.len().max(1) as f32;
+
+        self.caste_targets.iter()
+            .map(|(caste, target)| {
+                let count = castes.iter().filter(|c| *c == caste).count() as f32;
+                (*caste, target - count / total)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(Caste::Worker, |(caste, _)| caste)
+    }
+
+    /// Ages the brood, adults join the colony
+    /// Brood better off in the `chamber` is to be moved there
+    fn grow_brood(&mut self, chunk_mngr: &ChunkManager, chamber: (i32, i32, i32)) {
+        let mut newborns = vec![];
+        let jobs = &mut self.jobs;
+        let warmth = |temperature: f32| (temperature - IDEAL_TEMPERATURE).abs();
+        let chamber_warmth = warmth(chunk_mngr.temperature_at(chamber));
+
+        self.brood.retain_mut(|brood| {
+            let temperature = chunk_mngr.temperature_at(brood.pos);
+            match brood.tick(temperature) {
+                Growth::Growing => {
+                    if !Brood::is_comfortable(temperature) && warmth(temperature) > chamber_warmth {
+                        jobs.post(JobKind::MoveBrood, brood.pos, brood.pos, JobKind::MoveBrood.priority());
+                    }
+                    true
+                }
+                Growth::Emerged(caste) => {
+                    newborns.push(caste.hatch(brood.pos));
+                    false
+                }
+                Growth::Starved => false,
+            }
+        });
+
+        for ant in newborns {
+            self.recruit(ant);
+        }
+    }
+
     pub fn describe(&self) -> String {
         format!(
-            "New colony at : {:?}, it is named {:?} and has a population of {:?} ants\nIts queen has {} eggs ready to hatch",
+            "New colony at : {:?}, it is named {:?} and has a population of {:?} ants\nIts queen has {} eggs to lay, {} more are growing, {} food in stock",
             self.queen.pos,
             self.name,
             self.ants.len(),
-            self.queen.eggs.len()
+            self.queen.eggs.len(),
+            self.brood.len(),
+            self.food
         )
     }
-    // Colony::PLAYER
+    /// The player's colony
     pub fn bel_o_kan() -> Self {
         let mut bok = Self::new(Self::TEST_NAME, (0, 0, (SEA_LEVEL as i32) + 15));
+        bok.owner = Owner::Player;
+        bok.color = Color::RGB(60, 140, 255);
 
-        let ants = vec![];
-
-        bok.ants = ants;
-
-        bok.queen.eggs.push(Egg { hatch: Explorer::new });
-        // bok.queen.eggs.push(Egg { hatch: Explorer::new });
-        // bok.queen.eggs.push(Egg { hatch: Explorer::new });
-        // bok.queen.eggs.push(Egg { hatch: Worker::new });
-        // bok.queen.eggs.push(Egg { hatch: Worker::new });
-        // bok.queen.eggs.push(Egg { hatch: Worker::new });
-
-        println!("{}", bok.describe());
+        bok.food = Self::STARTING_FOOD;
+        // Somebody has to feed the first larvae
+        let nurse = Caste::Nurse.hatch(bok.queen.pos);
+        bok.recruit(nurse);
+        bok.queen.eggs.push(Caste::Explorer);
+        // bok.queen.eggs.push(Caste::Explorer);
+        // bok.queen.eggs.push(Caste::Explorer);
+        // bok.queen.eggs.push(Caste::Worker);
+        // bok.queen.eggs.push(Caste::Worker);
+        // bok.queen.eggs.push(Caste::Worker);
 
         bok
     }
 
-    // Colony::AI
     pub fn computer_colony() -> Self {
         let mut ai = Self::new(Self::AI_NAME, (-5, 0, (SEA_LEVEL as i32) - 15));
+        ai.owner = Owner::Ai;
+        ai.color = Color::RGB(255, 140, 0);
+
+        ai.food = Self::STARTING_FOOD;
+        ai.queen.eggs.push(Caste::Worker);
+        let nurse = Caste::Nurse.hatch(ai.queen.pos);
+        ai.recruit(nurse);
+
+        ai
+    }
+
+    /// Colony of a young queen back from her nuptial flight, she digs her chamber first
+    pub fn founded_by(name: &str, pos: (i32, i32, i32)) -> Self {
+        let mut colony = Self::new(name, pos);
+        colony.queen.founding = Some(Queen::FOUNDING_DEPTH);
+        colony.food = Self::FOUNDING_FOOD;
+        colony
+    }
 
-        let ants = vec![];
+    /// Wild colony, see AntManager::found to bring it into the world
+    pub fn new(name: &str, pos: (i32, i32, i32)) -> Self {
+        let mut colony = Colony {
+            id: 0,
+            name: name.to_string(),
+            color: Self::WILDLIFE_COLOR,
+            owner: Owner::Wildlife,
+            queen: Queen::new(pos),
+            ants: Ants::default(),
+            pathfinder: Pathfinder::default(),
+            pheromones: PheromoneField::default(),
+            food: 0,
+            storage: None,
+            brood: vec![],
+            nest_plan: None,
+            designations: Designations::default(),
+            jobs: JobBoard::default(),
+            // 0 is the queen
+            next_ant_id: 1,
+            corpses: vec![],
+            rally: None,
+            attacks: vec![],
+            caste_targets: Self::CASTE_TARGETS,
+            explored: ExploredMap::default(),
+            seed: 0,
+        };
+        colony.explored.reveal_around(pos, Self::NEST_SIGHT);
+        colony
+    }
+}
 
-        ai.ants = ants;
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{
+            apply_gravity,
+            brood::{ Brood, Caste, Stage },
+            colony::Colony,
+            designation::{ Area, Designation },
+            direction::Direction,
+            jobs::JobKind,
+            nest::NestPlan,
+            pheromone::{ Pheromone, PheromoneField },
+            spatial::SpatialIndex,
+            Action,
+            Cargo,
+            ColonyMember,
+            Context,
+        },
+        chunk::{ tile::Tile, ChunkManager },
+        time::TICKS_PER_MINUTE,
+    };
 
-        println!("{}", ai.describe());
+    #[test]
+    fn foraging_loop() {
+        let mngr = ChunkManager::flat_world();
+        mngr.set_tile((7, 2, 2), Tile::GRASS);
 
-        ai
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        let mut ant = Caste::Worker.hatch((6, 2, 2));
+        let pheromones = PheromoneField::default();
+        let ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: colony.queen.pos,
+            storage: colony.storage(),
+            food: colony.food,
+            tick: 0,
+            seed: 0,
+            needed_caste: colony.needed_caste(),
+            mound: (0, 2, 2),
+            enemies: &SpatialIndex::default(),
+            rally: None,
+            brood_chamber: (2, 2, 2),
+        };
+
+        let food = match ant.as_mut().think(&ctx) {
+            Some(Action::PickUp(food)) => food,
+            _ => panic!("Should pick the grass up"),
+        };
+        Colony::pick_up(&mut ant.as_mut(), food, &mngr);
+        assert_eq!(ant.cargo, Some(Cargo::Food(Colony::CARRY_CAPACITY)));
+        assert_eq!(mngr.tile_at(food).unwrap().food_quantity(), 5);
+
+        assert!(matches!(ant.as_mut().think(&ctx), Some(Action::GoTo((2, 2, 2)))));
+
+        // Nothing is stored away from home
+        assert_eq!(Colony::drop(&mut ant.as_mut(), ctx.storage), 0);
+
+        ant.pos = (2, 2, 2);
+        assert!(matches!(ant.as_mut().think(&ctx), Some(Action::Drop)));
+        colony.food += Colony::drop(&mut ant.as_mut(), ctx.storage);
+
+        assert_eq!(colony.food, Colony::CARRY_CAPACITY as u32);
+        assert_eq!(ant.cargo, None);
+    }
+
+    #[test]
+    fn starving_ants_die() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        colony.recruit(Caste::Worker.hatch((6, 2, 2)));
+        colony.recruit(Caste::Worker.hatch((7, 2, 2)));
+
+        colony.ants.get_mut(0).needs_mut().unwrap().satiety = 0.0;
+        // Both busy, nobody moves this tick
+        for i in 0..colony.ants.len() {
+            colony.ants.get_mut(i).set_next_action(1);
+        }
+        colony.tick(&mngr, 0, &SpatialIndex::default());
+
+        assert_eq!(colony.ants.len(), 1);
+        assert_eq!(colony.ants.positions(), &[(7, 2, 2)]);
+        assert_eq!(mngr.tile_at((6, 2, 2)), Some(Tile::DEAD_INSECT), "Should leave a corpse");
+    }
+
+    #[test]
+    fn actions_take_ticks() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        colony.recruit(Caste::Worker.hatch((6, 2, 2)));
+        // The flat world is too shallow for a nest
+        colony.nest_plan = Some(NestPlan::default());
+
+        colony.tick(&mngr, 0, &SpatialIndex::default());
+        let busy_until = colony.ants.get_mut(0).next_action();
+        let pos = colony.ants.positions()[0];
+        assert!(busy_until > 0);
+
+        // Nothing happens until it's done
+        for tick in 1..busy_until {
+            colony.tick(&mngr, tick, &SpatialIndex::default());
+        }
+        assert_eq!(colony.ants.positions()[0], pos);
+        assert_eq!(colony.ants.get_mut(0).next_action(), busy_until);
+
+        colony.tick(&mngr, busy_until, &SpatialIndex::default());
+        assert!(colony.ants.get_mut(0).next_action() > busy_until);
     }
 
-    pub fn new(name: &'static str, pos: (i32, i32, i32)) -> Self {
-        Colony {
-            name,
-            queen: Queen { pos, last_action: Instant::now(), eggs: vec![] },
-            ants: vec![],
+    #[test]
+    fn exhausted_ants_recover() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        colony.recruit(Caste::Worker.hatch((2, 2, 2)));
+        colony.nest_plan = Some(NestPlan::default());
+        colony.ants.get_mut(0).needs_mut().unwrap().energy = 0.0;
+
+        colony.tick(&mngr, 0, &SpatialIndex::default());
+        // Resting leaves no trail
+        assert_eq!(colony.pheromones.get((2, 2, 2), Pheromone::Home), 0.0);
+        for tick in 1..TICKS_PER_MINUTE * 2 {
+            colony.tick(&mngr, tick, &SpatialIndex::default());
+        }
+        assert!(!colony.ants.get_mut(0).needs_mut().unwrap().is_tired());
+    }
+
+    #[test]
+    fn walks_stay_out_of_keep_out() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        colony.recruit(Caste::Worker.hatch((5, 2, 2)));
+        colony.nest_plan = Some(NestPlan::default());
+        let area = Area::new((6, 0, 0), (6, 7, 10));
+        colony.designate(area, Designation::KeepOut, &mngr);
+
+        // Right next to it
+        colony.ants.get_mut(0).walk(&mngr, colony.pathfinder.keep_out(), Direction::East);
+        assert_eq!(colony.ants.positions()[0], (5, 2, 2));
+
+        // Even with a food trail leading through it
+        for x in 6..8 {
+            colony.pheromones.deposit((x, 2, 2), Pheromone::Food, 1000.0 * x as f32);
+        }
+        for tick in 0..TICKS_PER_MINUTE * 10 {
+            colony.tick(&mngr, tick, &SpatialIndex::default());
+            let pos = colony.ants.positions()[0];
+            assert!(!area.contains(pos), "Went into the keep out area at {:?}", pos);
         }
     }
+
+    #[test]
+    fn spoil_goes_to_the_mound() {
+        let mngr = ChunkManager::flat_world();
+        let mut ant = Caste::Worker.hatch((6, 2, 2));
+
+        // Dirt takes a few hits
+        assert!(!Colony::dig(&mut ant.as_mut(), (7, 2, 1), &mngr));
+        while !Colony::dig(&mut ant.as_mut(), (7, 2, 1), &mngr) {}
+        assert_eq!(mngr.tile_at((7, 2, 1)), Some(Tile::AIR));
+        assert_eq!(ant.cargo, Some(Cargo::Spoil(Tile::DIRT)));
+
+        // Hands are full
+        assert!(!Colony::dig(&mut ant.as_mut(), (6, 2, 1), &mngr));
+
+        let mound = (10, 2, 2);
+        ant.pos = mound;
+        assert!(Colony::dump(&mut ant.as_mut(), mound, &mngr));
+        assert_eq!(ant.cargo, None);
+
+        let piled = [(9, 2, 2), (11, 2, 2), (10, 1, 2), (10, 3, 2)]
+            .iter()
+            .filter(|p| mngr.tile_at(**p) == Some(Tile::DIRT))
+            .count();
+        assert_eq!(piled, 1, "Should be piled next to the ant");
+    }
+
+    #[test]
+    fn needed_caste_follows_targets() {
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        assert_eq!(colony.needed_caste(), Caste::Worker);
+
+        for _ in 0..4 {
+            colony.recruit(Caste::Worker.hatch((2, 2, 2)));
+        }
+        assert_eq!(colony.needed_caste(), Caste::Nurse);
+
+        // Eggs count too
+        colony.brood.push(Brood::egg((2, 2, 2), Caste::Nurse));
+        assert_eq!(colony.needed_caste(), Caste::Explorer);
+        colony.brood.push(Brood::egg((2, 2, 2), Caste::Explorer));
+        assert_eq!(colony.needed_caste(), Caste::Soldier);
+    }
+
+    #[test]
+    fn jobs_are_not_shared() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        colony.designate(Area::new((5, 2, 1), (6, 2, 1)), Designation::Dig, &mngr);
+        colony.recruit(Caste::Worker.hatch((4, 2, 2)));
+        colony.recruit(Caste::Worker.hatch((7, 2, 2)));
+        colony.post_jobs(&mngr);
+        assert_eq!(colony.jobs.jobs.len(), 2);
+
+        for i in 0..colony.ants.len() {
+            Colony::assign_job(&mut colony.jobs, &mut colony.ants.get_mut(i), 0);
+        }
+        let first = colony.jobs.reserved_by(1).unwrap().id;
+        let second = colony.jobs.reserved_by(2).unwrap().id;
+        assert_ne!(first, second);
+        assert_eq!(colony.jobs.reserved_by(1).unwrap().kind, JobKind::Dig);
+
+        // Back on the board when the ant dies
+        colony.ants.get_mut(0).needs_mut().unwrap().satiety = 0.0;
+        colony.bury_the_dead(&mngr);
+        assert!(colony.jobs.reserved_by(1).is_none());
+        assert!(colony.jobs.jobs.iter().any(|job| job.id == first && job.reserved.is_none()));
+
+        // Done by somebody else, the corpse comes first
+        mngr.set_tile((6, 2, 1), Tile::AIR);
+        colony.post_jobs(&mngr);
+        Colony::assign_job(&mut colony.jobs, &mut colony.ants.get_mut(0), 1);
+        let job = colony.jobs.reserved_by(2).unwrap();
+        assert_eq!((job.kind, job.target), (JobKind::Haul, (4, 2, 2)));
+        assert!(colony.jobs.jobs.iter().all(|job| job.id != second));
+    }
+
+    #[test]
+    fn nurses_feed_the_queen_and_move_brood() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::new("Test", (2, 2, 2));
+        colony.food = 10;
+        colony.queen.needs.satiety = 0.0;
+        colony.post_jobs(&mngr);
+
+        let job = colony.jobs.jobs[0];
+        assert_eq!((job.kind, job.target), (JobKind::Nurse, (2, 2, 2)));
+        // Workers don't nurse
+        colony.recruit(Caste::Worker.hatch((3, 2, 2)));
+        Colony::assign_job(&mut colony.jobs, &mut colony.ants.get_mut(0), 0);
+        assert!(colony.jobs.reserved_by(1).is_none());
+
+        // Carried around, and put down somewhere else
+        let mut nurse = Caste::Nurse.hatch((5, 2, 2));
+        colony.brood.push(Brood::egg((6, 2, 2), Caste::Worker));
+        Colony::carry(&mut nurse.as_mut(), (6, 2, 2), &mut colony.brood);
+        assert!(colony.brood.is_empty());
+        nurse.pos = (9, 4, 2);
+        colony.brood.extend(Colony::put_down(&mut nurse.as_mut()));
+        assert_eq!(colony.brood[0].pos, (9, 4, 2));
+        assert_eq!(nurse.cargo, None);
+    }
+
+    #[test]
+    fn young_queen_founds_alone() {
+        let mngr = ChunkManager::flat_world();
+        let mut colony = Colony::founded_by("Young", (2, 2, 2));
+        let pheromones = PheromoneField::default();
+        let ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: colony.queen.pos,
+            storage: colony.storage(),
+            food: colony.food,
+            tick: 0,
+            seed: 0,
+            needed_caste: Caste::Worker,
+            mound: colony.queen.pos,
+            enemies: &SpatialIndex::default(),
+            rally: None,
+            brood_chamber: colony.queen.pos,
+        };
+        assert!(matches!(colony.queen.think(&ctx), Some(Action::Dig((2, 2, 1)))));
+
+        // Dirt gives way, bedrock doesn't
+        for _ in 0..100 {
+            let (x, y, z) = colony.queen.pos;
+            Colony::dig_founding_chamber(&mut colony.queen, (x, y, z - 1), &mngr);
+            colony.queen.pos = apply_gravity(&colony.queen.pos, &mngr);
+            if colony.queen.founding.is_none() {
+                break;
+            }
+        }
+        assert!(colony.queen.founding.is_none());
+        assert_eq!(colony.queen.pos, (2, 2, 1));
+
+        // No nurse yet, she feeds her larvae herself
+        let mut larva = Brood::egg((2, 2, 1), Caste::Worker);
+        larva.stage = Stage::Larva;
+        larva.hunger = TICKS_PER_MINUTE * 2;
+        colony.brood.push(larva);
+        let used = Colony::raise_brood_alone(&mut colony.brood, colony.queen.pos, colony.food);
+        assert_eq!(used, 1);
+        assert!(!colony.brood[0].is_hungry());
+    }
+
+    #[test]
+    fn starting_colonies_are_described() {
+        let bok = Colony::bel_o_kan();
+        let description = bok.describe();
+        assert!(description.contains(&format!("named {:?}", Colony::TEST_NAME)));
+        assert!(description.contains("population of 1 ants"), "The nurse");
+        assert!(description.contains(&format!("1 eggs to lay, 0 more are growing, {} food", Colony::STARTING_FOOD)));
+    }
 }
@@ -0,0 +1,45 @@
+use crate::ant::{ brood::Caste, is_within_reach, Action, Context };
+
+type Pos = (i32, i32, i32);
+
+/// How far soldiers notice enemy ants
+pub const DETECTION_RADIUS: i32 = 6;
+
+/// Health and strength of a colony member
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub health: u32,
+    /// Damage dealt by each hit
+    pub attack: u32,
+}
+
+impl Stats {
+    pub const QUEEN: Stats = Stats { health: 60, attack: 4 };
+
+    pub fn of(caste: Caste) -> Self {
+        match caste {
+            Caste::Worker => Stats { health: 10, attack: 2 },
+            Caste::Explorer => Stats { health: 8, attack: 1 },
+            Caste::Soldier => Stats { health: 30, attack: 6 },
+            Caste::Nurse => Stats { health: 8, attack: 1 },
+            Caste::Drone => Stats { health: 4, attack: 0 },
+            Caste::Gyne => Stats { health: 20, attack: 2 },
+        }
+    }
+
+    pub fn hit(&mut self, damage: u32) {
+        self.health = self.health.saturating_sub(damage);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health == 0
+    }
+}
+
+/// Hits back an enemy standing right next to the ant
+pub fn fight_back(pos: Pos, ctx: &Context) -> Option<Action> {
+    ctx.enemies
+        .around(pos, 1)
+        .find(|p| is_within_reach(pos, *p))
+        .map(Action::Attack)
+}
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+
+use sdl2::pixels::Color;
+
+use crate::{
+    ant::{ apply_gravity, direction::Direction, nest::Dig },
+    chunk::{ tile::TileFlag, ChunkManager },
+    renderer::Renderer,
+};
+
+type Pos = (i32, i32, i32);
+
+/// What the player wants done in an area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Designation {
+    Dig,
+    /// Fill with spoil
+    Build,
+    /// Ants never walk in there
+    KeepOut,
+}
+
+impl Designation {
+    fn color(self) -> Color {
+        match self {
+            Designation::Dig => Color::RGBA(230, 180, 40, 120),
+            Designation::Build => Color::RGBA(40, 140, 230, 120),
+            Designation::KeepOut => Color::RGBA(230, 30, 30, 120),
+        }
+    }
+}
+
+/// Box of tiles, bounds included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Area {
+    /// From two opposite corners, in any order
+    pub fn new(a: Pos, b: Pos) -> Self {
+        Self {
+            min: (a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+            max: (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+        }
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        p.0 >= self.min.0 &&
+            p.0 <= self.max.0 &&
+            p.1 >= self.min.1 &&
+            p.1 <= self.max.1 &&
+            p.2 >= self.min.2 &&
+            p.2 <= self.max.2
+    }
+
+    /// Every tile, top layer first
+    pub fn tiles(&self) -> impl Iterator<Item = Pos> + '_ {
+        (self.min.2..=self.max.2)
+            .rev()
+            .flat_map(move |z| {
+                (self.min.1..=self.max.1).flat_map(move |y| {
+                    (self.min.0..=self.max.0).map(move |x| (x, y, z))
+                })
+            })
+    }
+}
+
+/// A single tile the player wants changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Job {
+    pub kind: Designation,
+    pub pos: Pos,
+}
+
+impl Job {
+    pub fn is_done(&self, chunk_mngr: &ChunkManager) -> bool {
+        let Some(tile) = chunk_mngr.tile_at(self.pos) else {
+            return false;
+        };
+        let is_free = tile.properties.contains(TileFlag::TRAVERSABLE);
+
+        match self.kind {
+            Designation::Dig => is_free || tile.hardness() == 0,
+            Designation::Build => !is_free,
+            Designation::KeepOut => true,
+        }
+    }
+
+    /// Somewhere an ant can stand to work on this tile
+    pub fn reachable_from(&self, chunk_mngr: &ChunkManager) -> Option<Pos> {
+        let (x, y, z) = self.pos;
+        let around = [Direction::North, Direction::East, Direction::South, Direction::West]
+            .iter()
+            .flat_map(|d| {
+                let (nx, ny, _) = d.add_to(&self.pos);
+                [(nx, ny, z), (nx, ny, z + 1)]
+            })
+            .chain([(x, y, z + 1)]);
+
+        let can_stand = |p: Pos| {
+            chunk_mngr.tile_at(p).is_some_and(|tile| tile.properties.contains(TileFlag::TRAVERSABLE)) &&
+                apply_gravity(&p, chunk_mngr) == p
+        };
+
+        around.filter(|p| *p != self.pos).find(|p| can_stand(*p))
+    }
+
+    pub fn as_dig(&self, chunk_mngr: &ChunkManager) -> Option<Dig> {
+        let from = self.reachable_from(chunk_mngr)?;
+        Some(Dig { tile: self.pos, from })
+    }
+}
+
+/// Areas designated by the player, and the jobs they made
+#[derive(Debug, Clone, Default)]
+pub struct Designations {
+    pub areas: Vec<(Area, Designation)>,
+    pub jobs: VecDeque<Job>,
+}
+
+impl Designations {
+    pub fn designate(&mut self, area: Area, kind: Designation, chunk_mngr: &ChunkManager) {
+        if kind != Designation::KeepOut {
+            for pos in area.tiles() {
+                let job = Job { kind, pos };
+                if !job.is_done(chunk_mngr) && !self.jobs.contains(&job) {
+                    self.jobs.push_back(job);
+                }
+            }
+        }
+        self.areas.push((area, kind));
+    }
+
+    /// Cancels every area containing `pos`, and their jobs
+    /// Returns whether anything was cancelled
+    pub fn cancel_at(&mut self, pos: Pos) -> bool {
+        let before = self.areas.len();
+        let (cancelled, kept) = self.areas.drain(..).partition(|(area, _)| area.contains(pos));
+        let cancelled: Vec<(Area, Designation)> = cancelled;
+        self.areas = kept;
+
+        self.jobs.retain(|job| {
+            !cancelled.iter().any(|(area, kind)| *kind == job.kind && area.contains(job.pos))
+        });
+        self.areas.len() != before
+    }
+
+    pub fn keep_out(&self) -> Vec<Area> {
+        self.areas
+            .iter()
+            .filter(|(_area, kind)| *kind == Designation::KeepOut)
+            .map(|(area, _kind)| *area)
+            .collect()
+    }
+
+    /// Forgets what's been done
+    pub fn prune(&mut self, chunk_mngr: &ChunkManager) {
+        self.jobs.retain(|job| !job.is_done(chunk_mngr));
+        let jobs = &self.jobs;
+        self.areas.retain(|(area, kind)| {
+            *kind == Designation::KeepOut || jobs.iter().any(|job| area.contains(job.pos))
+        });
+    }
+
+    /// First job of this kind an ant can work on
+    pub fn next(&self, kind: Designation, chunk_mngr: &ChunkManager) -> Option<Dig> {
+        self.jobs
+            .iter()
+            .filter(|job| job.kind == kind)
+            .find_map(|job| job.as_dig(chunk_mngr))
+    }
+
+    pub fn render(&self, renderer: &mut Renderer) {
+        let z = renderer.camera.2;
+
+        for (area, kind) in &self.areas {
+            if z < area.min.2 || z > area.max.2 {
+                continue;
+            }
+            for y in area.min.1..=area.max.1 {
+                for x in area.min.0..=area.max.0 {
+                    let (sx, sy) = renderer.tile_to_screen_coords((x, y));
+                    renderer.rect((sx, sy), kind.color());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::designation::{ Area, Designation, Designations },
+        chunk::{ tile::Tile, ChunkManager },
+    };
+
+    #[test]
+    fn designate_and_cancel() {
+        let mngr = ChunkManager::flat_world();
+        let mut designations = Designations::default();
+
+        designations.designate(Area::new((4, 3, 1), (2, 2, 1)), Designation::Dig, &mngr);
+        designations.designate(Area::new((8, 2, 2), (9, 2, 2)), Designation::Build, &mngr);
+        designations.designate(Area::new((0, 6, 2), (3, 7, 2)), Designation::KeepOut, &mngr);
+        assert_eq!(designations.jobs.len(), 6 + 2);
+        assert_eq!(designations.keep_out().len(), 1);
+
+        // Ants stand next to the tile
+        let dig = designations.next(Designation::Dig, &mngr).unwrap();
+        assert_eq!(dig.tile.2, 1);
+        assert_eq!(dig.from.2, 2);
+
+        // Built tiles are done
+        mngr.set_tile((8, 2, 2), Tile::DIRT);
+        mngr.set_tile((9, 2, 2), Tile::DIRT);
+        designations.prune(&mngr);
+        assert_eq!(designations.jobs.len(), 6);
+        assert!(designations.next(Designation::Build, &mngr).is_none());
+
+        assert!(designations.cancel_at((3, 3, 1)));
+        assert!(designations.jobs.is_empty());
+        assert!(!designations.cancel_at((3, 3, 1)));
+        assert_eq!(designations.areas.len(), 1, "Keep out stays");
+    }
+}
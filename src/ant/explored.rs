@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::{ ant::perception::Perception, chunk::{ ChunkManager, WIDTH } };
+
+// One bit per column of a chunk
+const _: () = assert!(WIDTH * WIDTH <= 64);
+
+/// Columns of the world a colony has discovered, stored by chunk like the world itself
+#[derive(Debug, Default, Clone)]
+pub struct ExploredMap {
+    chunks: HashMap<(i32, i32), u64>,
+}
+
+impl ExploredMap {
+    fn bit((x, y): (i32, i32)) -> ((i32, i32), u64) {
+        let (chunk_pos, (lx, ly, _)) = ChunkManager::to_local((x, y, 0));
+        (chunk_pos, 1 << (lx + ly * (WIDTH as i32)))
+    }
+
+    pub fn reveal(&mut self, column: (i32, i32)) {
+        let (chunk_pos, bit) = Self::bit(column);
+        *self.chunks.entry(chunk_pos).or_default() |= bit;
+    }
+
+    /// Everything within `radius` columns, seen or not
+    pub fn reveal_around(&mut self, (x, y, _): (i32, i32, i32), radius: i32) {
+        for x in x - radius..=x + radius {
+            for y in y - radius..=y + radius {
+                self.reveal((x, y));
+            }
+        }
+    }
+
+    /// Every tile an ant perceived
+    pub fn reveal_seen(&mut self, seen: &Perception) {
+        // Gathered by chunk first, an ant only sees a few of them
+        let mut chunks: Vec<((i32, i32), u64)> = vec![];
+        for ((x, y, _), _) in seen.tiles() {
+            let (chunk_pos, bit) = Self::bit((x, y));
+            match chunks.iter_mut().find(|(pos, _)| *pos == chunk_pos) {
+                Some((_, bits)) => *bits |= bit,
+                None => chunks.push((chunk_pos, bit)),
+            }
+        }
+        for (chunk_pos, bits) in chunks {
+            *self.chunks.entry(chunk_pos).or_default() |= bits;
+        }
+    }
+
+    pub fn is_explored(&self, column: (i32, i32)) -> bool {
+        let (chunk_pos, bit) = Self::bit(column);
+        self.chunks.get(&chunk_pos).is_some_and(|bits| bits & bit != 0)
+    }
+
+    /// Number of explored columns
+    pub fn count(&self) -> usize {
+        self.chunks.values().map(|bits| bits.count_ones() as usize).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::explored::ExploredMap;
+
+    #[test]
+    fn explored_columns() {
+        let mut map = ExploredMap::default();
+        assert!(!map.is_explored((0, 0)));
+
+        map.reveal_around((0, 0, 30), 1);
+        assert_eq!(map.count(), 9);
+        assert!(map.is_explored((-1, -1)), "Across chunk borders");
+        assert!(map.is_explored((1, 0)));
+        assert!(!map.is_explored((2, 0)));
+
+        // Nothing new
+        map.reveal((0, 0));
+        assert_eq!(map.count(), 9);
+    }
+}
@@ -0,0 +1,176 @@
+use serde::Deserialize;
+
+use crate::{ ant::brood::Caste, time::TICKS_PER_MINUTE };
+
+type Pos = (i32, i32, i32);
+
+/// Stable id of a colony member, the queen is 0
+pub type AntId = u32;
+
+/// A job nobody finished after that long goes back on the board
+pub const JOB_TIMEOUT: u64 = TICKS_PER_MINUTE * 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Dig,
+    /// Bring a corpse back to the stockpile
+    Haul,
+    /// Harvest food somebody found
+    Forage,
+    /// Feed a larva, or the queen
+    Nurse,
+    /// Carry a brood to a chamber with a better temperature
+    MoveBrood,
+    /// Stand somewhere dangerous
+    Guard,
+    /// Fill a tile with spoil
+    Build,
+}
+
+impl JobKind {
+    pub fn is_eligible(self, caste: Caste) -> bool {
+        match caste {
+            Caste::Worker => matches!(self, JobKind::Dig | JobKind::Haul | JobKind::Forage | JobKind::Build),
+            Caste::Nurse => matches!(self, JobKind::Nurse | JobKind::MoveBrood | JobKind::Haul),
+            Caste::Explorer | Caste::Soldier => self == JobKind::Guard,
+            // Waiting for the nuptial flight
+            Caste::Drone | Caste::Gyne => false,
+        }
+    }
+
+    /// Default priority, the higher the sooner
+    pub fn priority(self) -> u8 {
+        match self {
+            JobKind::Nurse => 5,
+            JobKind::Guard => 4,
+            JobKind::MoveBrood => 4,
+            JobKind::Haul => 3,
+            JobKind::Build => 3,
+            JobKind::Dig => 2,
+            JobKind::Forage => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Job {
+    pub id: u32,
+    pub kind: JobKind,
+    /// Tile to work on
+    pub target: Pos,
+    /// Where to stand to work on it
+    pub from: Pos,
+    pub priority: u8,
+    /// Who took it, and when
+    pub reserved: Option<(AntId, u64)>,
+}
+
+/// Everything a colony needs done
+#[derive(Debug, Clone, Default)]
+pub struct JobBoard {
+    pub jobs: Vec<Job>,
+    next_id: u32,
+}
+
+impl JobBoard {
+    /// Posts a job, unless the same one is already there
+    pub fn post(&mut self, kind: JobKind, target: Pos, from: Pos, priority: u8) {
+        if self.jobs.iter().any(|job| job.kind == kind && job.target == target) {
+            return;
+        }
+        self.jobs.push(Job { id: self.next_id, kind, target, from, priority, reserved: None });
+        self.next_id += 1;
+    }
+
+    /// Reserves the most urgent job this ant can do, the closest first
+    pub fn take(
+        &mut self,
+        ant: AntId,
+        caste: Caste,
+        pos: Pos,
+        tick: u64,
+        can_do: impl Fn(&Job) -> bool
+    ) -> Option<Job> {
+        let distance = |p: Pos| p.0.abs_diff(pos.0) + p.1.abs_diff(pos.1) + p.2.abs_diff(pos.2);
+
+        let job = self.jobs
+            .iter_mut()
+            .filter(|job| job.reserved.is_none() && job.kind.is_eligible(caste) && can_do(job))
+            .max_by_key(|job| (job.priority, u32::MAX - distance(job.from)))?;
+
+        job.reserved = Some((ant, tick));
+        Some(*job)
+    }
+
+    /// Job reserved by this ant, if any
+    pub fn reserved_by(&self, ant: AntId) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.reserved.is_some_and(|(id, _)| id == ant))
+    }
+
+    pub fn complete(&mut self, id: u32) {
+        self.jobs.retain(|job| job.id != id);
+    }
+
+    /// Puts this ant's jobs back on the board (it died, or gave up)
+    pub fn release(&mut self, ant: AntId) {
+        for job in self.jobs.iter_mut() {
+            if job.reserved.is_some_and(|(id, _)| id == ant) {
+                job.reserved = None;
+            }
+        }
+    }
+
+    /// Ants that took too long are probably stuck, somebody else will try
+    pub fn expire(&mut self, tick: u64) {
+        for job in self.jobs.iter_mut() {
+            if job.reserved.is_some_and(|(_, since)| tick >= since + JOB_TIMEOUT) {
+                job.reserved = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::{ brood::Caste, jobs::{ JobBoard, JobKind, JOB_TIMEOUT } };
+
+    #[test]
+    fn reservations_and_timeouts() {
+        let mut board = JobBoard::default();
+        board.post(JobKind::Dig, (5, 0, 1), (5, 0, 2), JobKind::Dig.priority());
+        board.post(JobKind::Dig, (1, 0, 1), (1, 0, 2), JobKind::Dig.priority());
+        board.post(JobKind::Nurse, (9, 9, 2), (9, 9, 2), JobKind::Nurse.priority());
+        // Already posted
+        board.post(JobKind::Dig, (1, 0, 1), (1, 0, 2), 0);
+        assert_eq!(board.jobs.len(), 3);
+
+        // Explorers don't dig nor nurse
+        assert!(board.take(1, Caste::Explorer, (0, 0, 2), 0, |_| true).is_none());
+
+        // Most urgent first, then the closest
+        let first = board.take(1, Caste::Nurse, (0, 0, 2), 0, |_| true).unwrap();
+        assert_eq!(first.kind, JobKind::Nurse);
+        assert!(board.take(1, Caste::Nurse, (0, 0, 2), 0, |_| true).is_none(), "Nurses don't dig");
+        let second = board.take(2, Caste::Worker, (0, 0, 2), 0, |_| true).unwrap();
+        assert_eq!(second.target, (1, 0, 1));
+        assert_eq!(board.reserved_by(2), Some(&board.jobs[1]));
+
+        // Nobody takes the same job twice
+        let third = board.take(3, Caste::Worker, (0, 0, 2), 0, |_| true).unwrap();
+        assert_eq!(third.target, (5, 0, 1));
+        assert!(board.take(4, Caste::Worker, (0, 0, 2), 0, |_| true).is_none());
+
+        // Ant 2 died
+        board.release(2);
+        assert!(board.take(4, Caste::Worker, (0, 0, 2), 10, |_| true).is_some());
+
+        board.complete(first.id);
+        assert_eq!(board.jobs.len(), 2);
+
+        // Ant 3 is stuck
+        board.expire(JOB_TIMEOUT);
+        assert!(board.reserved_by(3).is_none());
+        assert!(board.reserved_by(4).is_some(), "Not late yet");
+    }
+}
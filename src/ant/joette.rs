@@ -1,7 +1,5 @@
 #[allow(unused_imports)]
-use crate::ant::worker::Worker;
-#[allow(unused_imports)]
-use crate::ant::ColonyMember;
+use crate::ant::brood::Caste;
 #[allow(unused_imports)]
 use crate::{ chunk::SEA_LEVEL, Game };
 
@@ -11,14 +9,14 @@ fn joette_the_ant() -> Result<(), ()> {
     // Joette is born, she's a brave explorer
     // Born in the middle of nowhere,
     // she is seeking adventure and wants to discover the world
-    let joette = Worker::new(pos);
+    let joette = Caste::Worker.hatch(pos);
 
     // Game init
     let ttf_context = sdl2::ttf::init().expect("TTF init failed");
-    let mut game = Game::new(sdl2::init().unwrap(), &ttf_context);
+    let mut game = Game::new(sdl2::init().unwrap(), &ttf_context, Default::default());
 
     // Joette enters the game
-    game.ant_manager.colonies[0].ants.insert(0, joette);
+    game.ant_manager.player_mut().unwrap().recruit(joette);
 
     // Game starts
     game.run();
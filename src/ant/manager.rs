@@ -1,24 +1,369 @@
-use crate::{ ant::colony::Colony, chunk::ChunkManager, renderer::Renderer };
+use std::collections::HashMap;
+
+use rand::{ rngs::StdRng, Rng, SeedableRng };
+
+use crate::{
+    ant::{
+        ai::{ Controller, Difficulty },
+        brood::Caste,
+        colony::{ Colony, ColonyId, Owner },
+        spatial::SpatialIndex,
+    },
+    chunk::{ tile::TileType, ChunkManager },
+    renderer::Renderer,
+    time::{ self, Season },
+};
 
 pub struct Manager {
-    pub colonies: [Colony; 2], // One colony for each player (0 is player and other are AI for now)
+    pub colonies: Vec<Colony>,
+    /// Plays the colonies owned by the AI
+    pub controllers: HashMap<ColonyId, Controller>,
+    next_colony_id: ColonyId,
+    seed: u32,
+    difficulty: Difficulty,
+    /// Where the young queens land
+    rng: StdRng,
+    /// Year of the last nuptial flight
+    last_flight: Option<u64>,
 }
 
 impl Manager {
-    pub fn new() -> Self {
+    /// The player against one AI colony
+    pub fn new(seed: u32, difficulty: Difficulty) -> Self {
+        let mut mngr = Self::empty(seed, difficulty);
+        mngr.found(Colony::bel_o_kan());
+        mngr.found(Colony::computer_colony());
+        mngr
+    }
+
+    pub fn empty(seed: u32, difficulty: Difficulty) -> Self {
         Self {
-            colonies: [Colony::bel_o_kan(), Colony::computer_colony()],
+            colonies: vec![],
+            controllers: HashMap::new(),
+            next_colony_id: 0,
+            seed,
+            difficulty,
+            rng: StdRng::seed_from_u64(seed as u64),
+            last_flight: None,
+        }
+    }
+
+    /// Brings a new colony into the world, with its own id
+    pub fn found(&mut self, mut colony: Colony) -> ColonyId {
+        colony.id = self.next_colony_id;
+        self.next_colony_id += 1;
+
+        // Each colony rolls its own dice, and each AI plays its own game
+        colony.seed = (self.seed as u64) + (colony.id as u64);
+        if colony.owner == Owner::Ai {
+            self.controllers.insert(colony.id, Controller::new(self.difficulty, colony.seed));
         }
+        let id = colony.id;
+        self.colonies.push(colony);
+        id
+    }
+
+    pub fn get(&self, id: ColonyId) -> Option<&Colony> {
+        self.colonies.iter().find(|colony| colony.id == id)
+    }
+    pub fn get_mut(&mut self, id: ColonyId) -> Option<&mut Colony> {
+        self.colonies.iter_mut().find(|colony| colony.id == id)
+    }
+
+    pub fn player(&self) -> Option<&Colony> {
+        self.colonies.iter().find(|colony| colony.owner == Owner::Player)
+    }
+    pub fn player_mut(&mut self) -> Option<&mut Colony> {
+        self.colonies.iter_mut().find(|colony| colony.owner == Owner::Player)
     }
 }
 
 impl Manager {
-    pub fn tick(&mut self, chunk_mngr: &ChunkManager) {
-        self.colonies[Colony::PLAYER].tick(chunk_mngr);
-        self.colonies[Colony::AI].tick(chunk_mngr);
+    /// How far from home a young queen may land
+    const FLIGHT_RANGE: i32 = 24;
+    /// Young queens don't settle right next to another colony
+    const MIN_COLONY_DISTANCE: i32 = 8;
+
+    pub fn tick(&mut self, chunk_mngr: &ChunkManager, tick: u64) {
+        self.nuptial_flight(chunk_mngr, tick);
+
+        // Everybody sees where the others were at the start of the tick
+        let members: Vec<Vec<(i32, i32, i32)>> = self.colonies
+            .iter()
+            .map(|colony| colony.members())
+            .collect();
+
+        for (i, colony) in self.colonies.iter_mut().enumerate() {
+            let enemies = SpatialIndex::new(
+                members
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .flat_map(|(_, positions)| positions.iter().copied())
+            );
+            if let Some(ai) = self.controllers.get_mut(&colony.id) {
+                ai.tick(colony, chunk_mngr, &enemies, tick);
+            }
+            colony.tick(chunk_mngr, tick, &enemies);
+        }
+        self.resolve_combat();
+        self.remove_fallen();
+    }
+
+    /// Once a summer, the alates of every colony fly off and mate, then young queens found colonies
+    fn nuptial_flight(&mut self, chunk_mngr: &ChunkManager, tick: u64) {
+        let year = time::year(tick);
+        if time::season(tick) != Season::Summer || self.last_flight == Some(year) {
+            return;
+        }
+        self.last_flight = Some(year);
+
+        let mut gynes = vec![];
+        let mut drones = 0;
+        for colony in self.colonies.iter_mut() {
+            for (caste, pos) in colony.release_alates() {
+                match caste {
+                    Caste::Gyne => gynes.push((colony.name.clone(), pos)),
+                    _ => drones += 1,
+                }
+            }
+        }
+        // Drones die after mating, unmated gynes don't make it either
+        if drones == 0 {
+            return;
+        }
+
+        for (mother, (x, y, _)) in gynes {
+            let range = -Self::FLIGHT_RANGE..=Self::FLIGHT_RANGE;
+            let landing = (x + self.rng.gen_range(range.clone()), y + self.rng.gen_range(range));
+            let Some(pos) = self.landing_spot(landing, chunk_mngr) else {
+                continue;
+            };
+            let name = format!("{} {}", mother, self.next_colony_id);
+            eprintln!("A young queen from {} founded {}", mother, name);
+            self.found(Colony::founded_by(&name, pos));
+        }
+    }
+
+    /// Dry ground, far enough from every other queen
+    fn landing_spot(&self, (x, y): (i32, i32), chunk_mngr: &ChunkManager) -> Option<(i32, i32, i32)> {
+        let z = chunk_mngr.surface_z((x, y))? + 1;
+        let tile = chunk_mngr.tile_at((x, y, z))?;
+        if !matches!(tile.tile_type, TileType::Gas(_) | TileType::Food(_)) {
+            return None;
+        }
+
+        let too_close = self.colonies.iter().any(|colony| {
+            let (qx, qy, _) = colony.queen.pos;
+            (qx - x).abs().max((qy - y).abs()) < Self::MIN_COLONY_DISTANCE
+        });
+        (!too_close).then_some((x, y, z))
+    }
+
+    /// Each bite hurts the first enemy standing where it landed
+    fn resolve_combat(&mut self) {
+        for i in 0..self.colonies.len() {
+            let attacks = std::mem::take(&mut self.colonies[i].attacks);
+
+            for (pos, damage) in attacks {
+                let target = self.colonies
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .find_map(|(_, colony)| colony.member_at(pos));
+                if let Some(stats) = target {
+                    stats.hit(damage);
+                }
+            }
+        }
+    }
+
+    /// Colonies that lost their queen are gone, but the player's (see is_game_over)
+    fn remove_fallen(&mut self) {
+        let controllers = &mut self.controllers;
+
+        self.colonies.retain(|colony| {
+            if !colony.has_fallen() || colony.owner == Owner::Player {
+                return true;
+            }
+            eprintln!("The queen of {} is dead, the colony is no more", colony.name);
+            controllers.remove(&colony.id);
+            false
+        });
     }
+
+    /// The game is over when the player's queen is dead
+    pub fn is_game_over(&self) -> bool {
+        self.player().is_none_or(|colony| colony.has_fallen())
+    }
+
     pub fn render(&self, renderer: &mut Renderer, timestamp:f64) {
-        renderer.draw_ants(&self.colonies[Colony::PLAYER], timestamp);
-        renderer.draw_ants(&self.colonies[Colony::AI], timestamp);
+        for colony in self.colonies.iter().filter(|colony| colony.owner != Owner::Player) {
+            renderer.draw_ants(colony, timestamp);
+        }
+        // The player only sees what its explorers found, its own ants and designations on top
+        if let Some(player) = self.player() {
+            renderer.draw_fog(&player.explored);
+            renderer.draw_ants(player, timestamp);
+            player.designations.render(renderer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{
+            ai::Difficulty,
+            brood::Caste,
+            colony::{ Colony, Owner },
+            combat::Stats,
+            nest::NestPlan,
+            AntManager,
+        },
+        chunk::{ manager::LoadedChunk, tile::Tile, ChunkManager, WIDTH },
+        time::{ self, Season },
+    };
+
+    /// Flat ground wide enough for the young queens to land
+    fn wide_world() -> ChunkManager {
+        let mut mngr = ChunkManager::empty();
+        for cx in -4..4 {
+            for cy in -4..4 {
+                mngr.loaded_chunks.insert((cx, cy), LoadedChunk::new((cx, cy)));
+            }
+        }
+        let size = (WIDTH as i32) * 4;
+        for x in -size..size {
+            for y in -size..size {
+                mngr.set_tile((x, y, 0), Tile::BEDROCK);
+                mngr.set_tile((x, y, 1), Tile::DIRT);
+                mngr.set_tile((x, y, 2), Tile::DIRT);
+            }
+        }
+        mngr
+    }
+
+    /// Where every ant is after a couple of minutes
+    fn replay(seed: u32) -> Vec<(i32, i32, i32)> {
+        let mngr = wide_world();
+        let mut manager = AntManager::empty(seed, Difficulty::Normal);
+        for (name, x) in [("East", 8), ("West", -8)] {
+            let mut colony = Colony::new(name, (x, 0, 3));
+            // Too shallow for a nest
+            colony.nest_plan = Some(NestPlan::default());
+            colony.recruit(Caste::Explorer.hatch((x, 1, 3)));
+            colony.recruit(Caste::Explorer.hatch((x, -1, 3)));
+            colony.recruit(Caste::Soldier.hatch((x + 1, 0, 3)));
+            manager.found(colony);
+        }
+        for tick in 0..time::TICKS_PER_MINUTE * 2 {
+            manager.tick(&mngr, tick);
+        }
+        manager.colonies
+            .iter()
+            .flat_map(|colony| colony.ants.positions().iter().copied())
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let positions = replay(7);
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions, replay(7));
+        assert_ne!(positions, replay(8));
+    }
+
+    #[test]
+    fn soldiers_kill_the_queen() {
+        let mngr = ChunkManager::flat_world();
+        let mut manager = AntManager::empty(0, Difficulty::Easy);
+        let mut player = Colony::new("Player", (2, 2, 2));
+        player.owner = Owner::Player;
+        let player = manager.found(player);
+        let wild = manager.found(Colony::new("Wild", (12, 2, 2)));
+
+        manager.get_mut(player).unwrap().recruit(Caste::Worker.hatch((3, 2, 2)));
+        manager.get_mut(wild).unwrap().recruit(Caste::Soldier.hatch((4, 2, 2)));
+
+        // Bites land on the next tick
+        manager.get_mut(wild).unwrap().attacks.push(((3, 2, 2), 6));
+        manager.get_mut(player).unwrap().attacks.push(((4, 2, 2), 2));
+        manager.resolve_combat();
+        assert_eq!(manager.get_mut(player).unwrap().ants.stats_mut()[0].health, 4);
+        assert_eq!(manager.get_mut(wild).unwrap().ants.stats_mut()[0].health, 28);
+
+        // The worker dies, the queen is next
+        manager.get_mut(wild).unwrap().attacks.push(((3, 2, 2), 6));
+        manager.resolve_combat();
+        manager.tick(&mngr, 0);
+        assert!(manager.get(player).unwrap().ants.is_empty());
+        assert!(!manager.is_game_over());
+
+        for _ in 0..Stats::QUEEN.health / 6 + 1 {
+            manager.get_mut(wild).unwrap().attacks.push(((2, 2, 2), 6));
+            manager.resolve_combat();
+        }
+        assert!(manager.is_game_over());
+    }
+
+    #[test]
+    fn colonies_come_and_go() {
+        let mngr = ChunkManager::flat_world();
+        let mut manager = AntManager::new(0, Difficulty::Normal);
+        assert_eq!(manager.colonies.len(), 2);
+        assert_eq!(manager.controllers.len(), 1, "One AI");
+
+        let mut founded = Colony::new("Young", (10, 4, 2));
+        founded.owner = Owner::Ai;
+        let id = manager.found(founded);
+        assert_eq!(id, 2);
+        assert_eq!(manager.get(id).unwrap().name, "Young");
+
+        // Ids stay when a colony falls
+        manager.get_mut(1).unwrap().queen.stats.health = 0;
+        manager.tick(&mngr, 0);
+        assert!(manager.get(1).is_none());
+        assert_eq!(manager.get(id).unwrap().name, "Young");
+        assert_eq!(manager.controllers.len(), 1);
+        assert!(!manager.is_game_over());
+    }
+
+    #[test]
+    fn nuptial_flight() {
+        let mngr = wide_world();
+        let summer = time::start_of(Season::Summer, 0);
+        let mut manager = AntManager::empty(0, Difficulty::Normal);
+        let home = manager.found(Colony::new("Home", (0, 0, 3)));
+        for _ in 0..6 {
+            manager.get_mut(home).unwrap().recruit(Caste::Gyne.hatch((0, 0, 3)));
+        }
+
+        // No drone, no mating
+        manager.nuptial_flight(&mngr, summer);
+        assert!(manager.get(home).unwrap().ants.is_empty());
+        assert_eq!(manager.colonies.len(), 1);
+
+        let mut manager = AntManager::empty(0, Difficulty::Normal);
+        let home = manager.found(Colony::new("Home", (0, 0, 3)));
+        manager.get_mut(home).unwrap().recruit(Caste::Drone.hatch((0, 0, 3)));
+        for _ in 0..6 {
+            manager.get_mut(home).unwrap().recruit(Caste::Gyne.hatch((0, 0, 3)));
+        }
+        // Not before summer
+        manager.nuptial_flight(&mngr, time::start_of(Season::Spring, 0));
+        assert_eq!(manager.get(home).unwrap().ants.len(), 7);
+
+        manager.nuptial_flight(&mngr, summer);
+        assert!(manager.get(home).unwrap().ants.is_empty());
+        assert!(manager.colonies.len() > 1, "At least one queen landed");
+        for (i, colony) in manager.colonies.iter().enumerate().skip(1) {
+            assert_eq!(colony.owner, Owner::Wildlife);
+            assert!(colony.queen.founding.is_some());
+            for other in manager.colonies[..i].iter() {
+                let (dx, dy) = (colony.queen.pos.0 - other.queen.pos.0, colony.queen.pos.1 - other.queen.pos.1);
+                assert!(dx.abs().max(dy.abs()) >= 8);
+            }
+        }
     }
 }
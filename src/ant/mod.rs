@@ -1,36 +1,237 @@
 mod joette;
 pub mod direction;
+pub mod ai;
 
 pub mod colony;
 mod manager;
 mod queen;
-mod worker;
-mod explorer;
+pub mod pathfinding;
+pub mod pheromone;
+pub mod needs;
+pub mod brood;
+pub mod nest;
+pub mod designation;
+pub mod jobs;
+pub mod combat;
+pub mod behavior;
+pub mod perception;
+pub mod explored;
+pub mod store;
+pub mod spatial;
+
+#[cfg(test)]
+mod bench;
 
 mod render;
 
-use std::{ any::Any, time::Instant };
+use rand::{ rngs::StdRng, SeedableRng };
 
 /// Name export so it's not confused with Chunk::Manager
 pub use manager::Manager as AntManager;
 
-use crate::{ ant::direction::Direction, chunk::{ tile::TileFlag, ChunkManager } };
+use crate::{
+    ant::{
+        behavior::{ Blackboard, Task },
+        brood::{ Brood, Caste },
+        combat::Stats,
+        designation::Area,
+        direction::Direction,
+        jobs::{ AntId, Job },
+        needs::Needs,
+        pathfinding::{ moves_from, Path, Pathfinder, Search },
+        perception::{ Memory, Perception },
+        pheromone::{ Pheromone, PheromoneField },
+        spatial::SpatialIndex,
+    },
+    chunk::{ tile::{ Tile, TileFlag }, ChunkManager },
+    time::TICKS_PER_MINUTE,
+};
 #[allow(unused)]
 use crate::renderer::{ self, Renderer };
 
-pub trait ColonyMember: Any {
-    fn as_any(&self) -> &dyn Any;
-    fn as_any_mut(&mut self) -> &mut dyn Any;
+/// What an ant can sense of the world when it thinks
+pub struct Context<'a> {
+    pub chunk_mngr: &'a ChunkManager,
+    /// Its own colony's pheromones
+    pub pheromones: &'a PheromoneField,
+    /// Where the queen is
+    pub nest: (i32, i32, i32),
+    /// Where food is brought back
+    pub storage: (i32, i32, i32),
+    /// Food left in the stockpile
+    pub food: u32,
+    pub tick: u64,
+    /// The colony's share of the world seed, see sim_rng
+    pub seed: u64,
+    /// Caste the colony is the most short of
+    pub needed_caste: Caste,
+    /// Top of the spoil mound
+    pub mound: (i32, i32, i32),
+    /// Every ant of the other colonies
+    pub enemies: &'a SpatialIndex,
+    /// Where soldiers gather for a raid, if any
+    pub rally: Option<(i32, i32, i32)>,
+    /// Where the temperature suits the brood best
+    pub brood_chamber: (i32, i32, i32),
+}
+
+/// Something an ant carries around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cargo {
+    Food(u8),
+    /// Dug out earth or stone
+    Spoil(Tile),
+    /// An egg, larva or pupa being moved
+    Brood(Brood),
+}
+
+/// What the queen and the ants (see store::AntMut) have in common
+pub trait ColonyMember {
+    fn id(&self) -> AntId;
 
-    fn reset_last_action(&mut self);
+    /// Busy until this tick
+    fn set_next_action(&mut self, tick: u64);
     fn pos(&self) -> (i32, i32, i32);
     fn set_pos(&mut self, pos: (i32, i32, i32));
 
-    fn think(&mut self) -> Option<Action>;
-    fn render(&self, renderer: &mut Renderer);
+    fn next_action(&self) -> u64;
+
+    /// Looks around, then thinks about what it saw
+    fn think(&mut self, ctx: &Context) -> Option<Action> {
+        let seen = Perception::sense(self.pos(), perception::sight(self.caste()?), ctx);
+        self.think_about(&seen, ctx)
+    }
+
+    /// Runs the behavior tree of its caste (see behavior.rs)
+    fn think_about(&mut self, seen: &Perception, ctx: &Context) -> Option<Action> {
+        let caste = self.caste()?;
+        let pos = self.pos();
+        let memory = self.memory_mut().map(|memory| {
+            memory.remember(pos, seen, ctx);
+            memory.clone()
+        });
+        let path = self.path_mut().as_ref();
+        let heading = path.filter(|path| !path.is_done()).map(|path| path.goal);
+        let is_lost = path.is_some_and(|path| path.is_done() && path.goal != pos);
+
+        let mut board = Blackboard {
+            ctx,
+            pos,
+            carrying: self.cargo_mut().and_then(|cargo| *cargo),
+            job: self.job_mut().and_then(|job| *job),
+            needs: self.needs_mut().map(|needs| *needs),
+            heading,
+            is_lost,
+            seen,
+            memory: memory.unwrap_or_default(),
+            rng: sim_rng(ctx.seed, self.id(), ctx.tick),
+        };
+        let (action, node) = behavior::tree(caste).decide(&mut board);
+        self.set_node(node);
+        action
+    }
+
+    /// Task the ant was last busy with, for the debug view
+    fn set_node(&mut self, _node: Option<Task>) {}
+
+    /// One step, climbing if there is a wall
+    /// Steps or climbs one tile, unless the player kept it out
+    fn walk(&mut self, chunk_mngr: &ChunkManager, keep_out: &[Area], direction: Direction) {
+        let dest = direction.add_to(&self.pos());
+        let allowed = |p: (i32, i32, i32)| !keep_out.iter().any(|area| area.contains(p));
+
+        if let Some(tile) = chunk_mngr.tile_at(dest) {
+            if tile.properties.contains(TileFlag::TRAVERSABLE) {
+                if allowed(dest) {
+                    self.set_pos(dest);
+                }
+            } else {
+                let climb_dest = Direction::Up.add_to(&dest);
+                if let Some(climb_tile) = chunk_mngr.tile_at(climb_dest) {
+                    if climb_tile.properties.contains(TileFlag::TRAVERSABLE) && allowed(climb_dest) {
+                        self.set_pos(climb_dest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Health and attack
+    fn stats_mut(&mut self) -> &mut Stats;
+
+    /// Pheromone left behind while moving, if any
+    fn trail(&self) -> Option<Pheromone> {
+        None
+    }
+
+    /// What this ant carries, None if it can't carry anything
+    fn cargo_mut(&mut self) -> Option<&mut Option<Cargo>> {
+        None
+    }
+
+    /// None for the queen
+    fn caste(&self) -> Option<Caste> {
+        None
+    }
+
+    /// Job taken from the colony board, None for ants that never work
+    fn job_mut(&mut self) -> Option<&mut Option<Job>> {
+        None
+    }
+
+    /// Hunger, rest and age, None for ants that don't need anything
+    fn needs_mut(&mut self) -> Option<&mut Needs> {
+        None
+    }
+
+    /// Food, enemies and the way home seen lately, None for ants that forget everything
+    fn memory_mut(&mut self) -> Option<&mut Memory> {
+        None
+    }
+
+    /// Path currently followed, if any
+    fn path_mut(&mut self) -> &mut Option<Path>;
+
+    /// Takes one step towards `goal`, looking for a path first if needed
+    fn follow_path(
+        &mut self,
+        goal: (i32, i32, i32),
+        chunk_mngr: &ChunkManager,
+        pathfinder: &mut Pathfinder
+    ) {
+        let pos = self.pos();
+
+        if self.path_mut().as_ref().is_none_or(|path| path.goal != goal) {
+            *self.path_mut() = match pathfinder.find(pos, goal, chunk_mngr) {
+                Search::Found(path) => Some(path),
+                // Nothing to follow, think() will pick something else
+                Search::Unreachable => Some(Path::empty(goal)),
+                Search::Later => None,
+            };
+        }
+
+        let Some(next) = self.path_mut().as_mut().and_then(|path| path.steps.pop_front()) else {
+            return;
+        };
 
-    fn walk(&mut self, chunk_mngr: &ChunkManager, direction:Direction);
-    fn last_action(&self) -> Instant;
+        if moves_from(pos, chunk_mngr).iter().any(|(p, _cost)| *p == next) {
+            self.set_pos(next);
+        } else {
+            // The world changed under our feet, look for another path next time
+            *self.path_mut() = None;
+        }
+    }
+}
+
+/// Dice of one ant for one tick, the same on every run with the same seed
+pub fn sim_rng(seed: u64, id: AntId, tick: u64) -> StdRng {
+    let id = (id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    StdRng::seed_from_u64(seed ^ id ^ tick.rotate_left(32))
+}
+
+/// An ant reaches the tiles right next to it, one tile up or down included
+pub fn is_within_reach(ant: (i32, i32, i32), p: (i32, i32, i32)) -> bool {
+    ant.0.abs_diff(p.0) + ant.1.abs_diff(p.1) <= 1 && ant.2.abs_diff(p.2) <= 1
 }
 
 fn apply_gravity(pos: &(i32, i32, i32), chunk_mngr: &ChunkManager) -> (i32, i32, i32) {
@@ -57,5 +258,44 @@ fn apply_gravity(pos: &(i32, i32, i32), chunk_mngr: &ChunkManager) -> (i32, i32,
 }
 pub enum Action {
     Walk(Direction),
-    Breed(Vec<Box<dyn ColonyMember>>),
+    /// Follow a path to this position (see ColonyMember::follow_path)
+    GoTo((i32, i32, i32)),
+    /// Take food from this tile
+    PickUp((i32, i32, i32)),
+    /// Take the brood at this position
+    Carry((i32, i32, i32)),
+    /// Hit this tile, carrying the spoil away once it breaks
+    Dig((i32, i32, i32)),
+    /// Fill this tile with the carried spoil
+    Build((i32, i32, i32)),
+    /// Put down whatever is carried
+    Drop,
+    /// Eat from the stockpile
+    Eat,
+    /// Sleep in the nest
+    Rest,
+    /// Feed the larva at this position with food from the stockpile
+    Feed((i32, i32, i32)),
+    /// Bite the enemy ant at this position
+    Attack((i32, i32, i32)),
+    /// Lay eggs
+    Breed(Vec<Brood>),
+}
+
+impl Action {
+    /// Ticks spent thinking when there's nothing to do
+    pub const IDLE: u64 = 10;
+
+    /// Ticks the ant is busy for after doing it
+    pub fn duration(&self) -> u64 {
+        match self {
+            Action::Walk(_) | Action::GoTo(_) => 10,
+            Action::Attack(_) => 15,
+            Action::PickUp(_) | Action::Carry(_) | Action::Drop | Action::Feed(_) => 20,
+            // Short enough to rest more than is spent meanwhile
+            Action::Rest => 20,
+            Action::Dig(_) | Action::Build(_) | Action::Eat => 30,
+            Action::Breed(_) => TICKS_PER_MINUTE,
+        }
+    }
 }
@@ -0,0 +1,132 @@
+use crate::{ ant::{ Action, Context }, time::TICKS_PER_MINUTE };
+
+/// How full (or rested) an ant can be
+pub const MAX_NEED: f32 = 100.0;
+/// Below this, an ant stops what it is doing to take care of itself
+const LOW_NEED: f32 = 30.0;
+
+/// A full ant starves in 10 minutes
+const HUNGER_PER_TICK: f32 = MAX_NEED / ((TICKS_PER_MINUTE * 10) as f32);
+/// A rested ant is exhausted in 5 minutes
+const FATIGUE_PER_TICK: f32 = MAX_NEED / ((TICKS_PER_MINUTE * 5) as f32);
+/// Energy gained each time an ant rests
+const REST_GAIN: f32 = 20.0;
+/// Food from the stockpile an ant eats at once, each unit fills FOOD_VALUE
+const MEAL_SIZE: u32 = 2;
+const FOOD_VALUE: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Death {
+    Starvation,
+    OldAge,
+    /// By an enemy ant
+    Killed,
+}
+
+/// Hunger, rest and age of a colony member
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Needs {
+    /// 0 is starving
+    pub satiety: f32,
+    /// 0 is exhausted
+    pub energy: f32,
+    /// In ticks
+    pub age: u64,
+    pub lifespan: u64,
+}
+
+impl Needs {
+    pub fn new(lifespan: u64) -> Self {
+        Self { satiety: MAX_NEED, energy: MAX_NEED, age: 0, lifespan }
+    }
+
+    pub fn tick(&mut self) {
+        self.satiety = (self.satiety - HUNGER_PER_TICK).max(0.0);
+        self.energy = (self.energy - FATIGUE_PER_TICK).max(0.0);
+        self.age += 1;
+    }
+
+    pub fn is_hungry(&self) -> bool {
+        self.satiety < LOW_NEED
+    }
+    pub fn is_tired(&self) -> bool {
+        self.energy < LOW_NEED
+    }
+
+    pub fn cause_of_death(&self) -> Option<Death> {
+        if self.satiety <= 0.0 {
+            Some(Death::Starvation)
+        } else if self.age >= self.lifespan {
+            Some(Death::OldAge)
+        } else {
+            None
+        }
+    }
+
+    /// Eats from a `stock` of food, returns how much was eaten
+    pub fn eat(&mut self, stock: u32) -> u32 {
+        let missing = ((MAX_NEED - self.satiety) / FOOD_VALUE).ceil() as u32;
+        let eaten = missing.min(MEAL_SIZE).min(stock);
+
+        self.satiety = (self.satiety + (eaten as f32) * FOOD_VALUE).min(MAX_NEED);
+        eaten
+    }
+
+    pub fn rest(&mut self) {
+        self.energy = (self.energy + REST_GAIN).min(MAX_NEED);
+    }
+
+    /// Eat at the storage, sleep in the nest, or None if everything is fine
+    pub fn tend(&self, pos: (i32, i32, i32), ctx: &Context) -> Option<Action> {
+        let is_at = |goal: (i32, i32, i32)| (pos.0, pos.1) == (goal.0, goal.1);
+
+        if self.is_hungry() && ctx.food > 0 {
+            if is_at(ctx.storage) {
+                return Some(Action::Eat);
+            }
+            return Some(Action::GoTo(ctx.storage));
+        }
+        if self.is_tired() {
+            if is_at(ctx.nest) {
+                return Some(Action::Rest);
+            }
+            return Some(Action::GoTo(ctx.nest));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::needs::{ Death, Needs, MAX_NEED };
+
+    #[test]
+    fn hunger_rest_and_death() {
+        let mut needs = Needs::new(u64::MAX);
+        while !needs.is_hungry() {
+            needs.tick();
+        }
+        assert!(needs.is_tired(), "Ants get tired faster than hungry");
+
+        // Not enough food for a full meal
+        assert_eq!(needs.eat(1), 1);
+        assert_eq!(needs.eat(100), 2);
+        assert!(!needs.is_hungry());
+
+        needs.rest();
+        needs.rest();
+        assert!(!needs.is_tired());
+
+        while needs.cause_of_death().is_none() {
+            needs.tick();
+        }
+        assert_eq!(needs.cause_of_death(), Some(Death::Starvation));
+
+        let mut old = Needs::new(10);
+        for _ in 0..10 {
+            old.tick();
+        }
+        assert!(old.satiety < MAX_NEED);
+        assert_eq!(old.cause_of_death(), Some(Death::OldAge));
+    }
+}
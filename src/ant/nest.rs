@@ -0,0 +1,135 @@
+use std::collections::{ HashSet, VecDeque };
+
+use crate::chunk::{ tile::TileFlag, ChunkManager };
+
+type Pos = (i32, i32, i32);
+
+/// Steps of the staircase going down from the nest
+const STAIRS: i32 = 6;
+const CHAMBER_RADIUS: i32 = 2;
+/// Spoil is dumped that far from the nest, on the other side of the stairs
+const MOUND_DISTANCE: i32 = 3;
+
+/// A tile to dig, and where to stand to dig it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dig {
+    pub tile: Pos,
+    pub from: Pos,
+}
+
+/// Tunnels and chambers a colony wants, in digging order
+#[derive(Debug, Clone, Default)]
+pub struct NestPlan {
+    pub digs: Vec<Dig>,
+}
+
+impl NestPlan {
+    /// Staircase going down east of the nest, ending in a chamber
+    /// `nest` is where the queen stands, right above the ground
+    pub fn around(nest: Pos) -> Self {
+        let (x, y, z) = nest;
+        let mut digs = vec![];
+
+        // Each step : dig in front, then below, so ants can climb back up
+        for i in 1..=STAIRS {
+            let from = (x + i - 1, y, z - i + 1);
+            digs.push(Dig { tile: (x + i, y, z - i + 1), from });
+            digs.push(Dig { tile: (x + i, y, z - i), from });
+        }
+
+        let bottom = (x + STAIRS, y, z - STAIRS);
+        let is_in_chamber = |(cx, cy, _): Pos| {
+            cx > bottom.0 &&
+                cx <= bottom.0 + 2 * CHAMBER_RADIUS + 1 &&
+                (cy - bottom.1).abs() <= CHAMBER_RADIUS
+        };
+
+        // Dug from the inside out
+        let mut seen = HashSet::from([bottom]);
+        let mut queue = VecDeque::from([bottom]);
+        while let Some(from) = queue.pop_front() {
+            for (dx, dy) in [(1, 0), (0, 1), (0, -1), (-1, 0)] {
+                let tile = (from.0 + dx, from.1 + dy, from.2);
+                if is_in_chamber(tile) && seen.insert(tile) {
+                    digs.push(Dig { tile, from });
+                    queue.push_back(tile);
+                }
+            }
+        }
+
+        Self { digs }
+    }
+
+    /// Next tile still to dig, if any
+    pub fn next(&self, chunk_mngr: &ChunkManager) -> Option<Dig> {
+        self.digs
+            .iter()
+            .find(|dig| {
+                chunk_mngr.tile_at(dig.tile).is_some_and(|tile| {
+                    !tile.properties.contains(TileFlag::TRAVERSABLE) && tile.hardness() > 0
+                })
+            })
+            .copied()
+    }
+
+    /// Tiles that can be dug right now : still there, and with somewhere to stand
+    pub fn available<'a>(&'a self, chunk_mngr: &'a ChunkManager) -> impl Iterator<Item = Dig> + 'a {
+        let is_free = |p: Pos| {
+            chunk_mngr.tile_at(p).is_some_and(|tile| tile.properties.contains(TileFlag::TRAVERSABLE))
+        };
+
+        self.digs
+            .iter()
+            .filter(move |dig| {
+                !is_free(dig.tile) &&
+                    chunk_mngr.tile_at(dig.tile).is_some_and(|tile| tile.hardness() > 0) &&
+                    is_free(dig.from)
+            })
+            .copied()
+    }
+
+    /// Where spoil is dumped, on the surface
+    pub fn mound(nest: Pos) -> Pos {
+        (nest.0 - MOUND_DISTANCE, nest.1, nest.2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{ nest::{ NestPlan, STAIRS }, pathfinding::find_path },
+        chunk::{ tile::Tile, ChunkManager },
+    };
+
+    #[test]
+    fn dug_nest_is_walkable() {
+        let mngr = ChunkManager::flat_world();
+        // Some earth to dig in
+        for x in 0..16 {
+            for y in 0..8 {
+                for z in 2..10 {
+                    mngr.set_tile((x, y, z), Tile::DIRT);
+                }
+            }
+        }
+        let nest = (1, 4, 10);
+        let plan = NestPlan::around(nest);
+        let planned = plan.digs
+            .iter()
+            .filter(|dig| mngr.tile_at(dig.tile) == Some(Tile::DIRT))
+            .count();
+
+        let mut hits = 0;
+        while let Some(dig) = plan.next(&mngr) {
+            hits += 1;
+            mngr.dig(dig.tile, 50);
+        }
+        // Dirt takes 4 hits
+        assert_eq!(hits, planned * 4);
+        assert_eq!(mngr.tile_at((1 + STAIRS + 1, 4, 10 - STAIRS)), Some(Tile::AIR));
+
+        let chamber = (1 + STAIRS + 3, 5, 10 - STAIRS);
+        assert!(find_path(nest, chamber, &mngr, &[], 1_000).0.is_some(), "Should go down");
+        assert!(find_path(chamber, nest, &mngr, &[], 1_000).0.is_some(), "Should climb back up");
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{ BinaryHeap, HashMap, VecDeque };
+use std::cmp::Reverse;
+
+use crate::{
+    ant::{ apply_gravity, designation::Area, direction::Direction },
+    chunk::{ tile::TileFlag, ChunkManager },
+};
+
+/// Nodes a single search may expand before giving up
+const MAX_SEARCH_NODES: usize = 2_000;
+/// Nodes a colony may expand each tick (all ants together)
+const TICK_BUDGET: usize = 4_000;
+/// The cache is flushed when it gets bigger than this
+const MAX_CACHED_PATHS: usize = 256;
+
+const WALK_COST: u32 = 1;
+const CLIMB_COST: u32 = 2;
+
+type Pos = (i32, i32, i32);
+
+/// Steps left to reach `goal`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub goal: Pos,
+    pub steps: VecDeque<Pos>,
+}
+
+impl Path {
+    /// Nothing left to walk : the goal is reached (or could not be)
+    pub fn empty(goal: Pos) -> Self {
+        Self { goal, steps: VecDeque::new() }
+    }
+    pub fn is_done(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+pub enum Search {
+    Found(Path),
+    Unreachable,
+    /// Not enough budget left this tick, ask again later
+    Later,
+}
+
+/// Every position an ant standing at `pos` can end up at in one step :
+/// walking, climbing a single tile, and falling (see apply_gravity)
+pub fn moves_from(pos: Pos, chunk_mngr: &ChunkManager) -> Vec<(Pos, u32)> {
+    let mut moves = Vec::with_capacity(4);
+    let is_free = |p: Pos| {
+        chunk_mngr
+            .tile_at(p)
+            .is_some_and(|t| t.properties.contains(TileFlag::TRAVERSABLE))
+    };
+
+    for direction in [Direction::North, Direction::East, Direction::South, Direction::West] {
+        let dest = direction.add_to(&pos);
+
+        let Some(tile) = chunk_mngr.tile_at(dest) else {
+            // Unloaded chunk
+            continue;
+        };
+        if tile.properties.contains(TileFlag::TRAVERSABLE) {
+            moves.push((apply_gravity(&dest, chunk_mngr), WALK_COST));
+        } else {
+            let climb_dest = Direction::Up.add_to(&dest);
+            if is_free(climb_dest) {
+                moves.push((climb_dest, CLIMB_COST));
+            }
+        }
+    }
+    moves
+}
+
+fn heuristic(p: Pos, goal: Pos) -> u32 {
+    p.0.abs_diff(goal.0) + p.1.abs_diff(goal.1)
+}
+
+/// A* from `start` to `goal`, never going through `avoid`
+/// Returns the number of expanded nodes along with the path
+pub fn find_path(
+    start: Pos,
+    goal: Pos,
+    chunk_mngr: &ChunkManager,
+    avoid: &[Area],
+    max_nodes: usize
+) -> (Option<Path>, usize) {
+    let mut open = BinaryHeap::with_capacity(64);
+    // Cheapest cost found to each position, and where it was reached from
+    let mut best: HashMap<Pos, (u32, Pos)> = HashMap::with_capacity(64);
+    let mut expanded = 0;
+
+    open.push(Reverse((heuristic(start, goal), heuristic(start, goal), start)));
+    best.insert(start, (0, start));
+
+    while let Some(Reverse((f, h, current))) = open.pop() {
+        let current_cost = f - h;
+        if current == goal {
+            let mut steps = VecDeque::new();
+            let mut p = current;
+            while p != start {
+                steps.push_front(p);
+                p = best[&p].1;
+            }
+            return (Some(Path { goal, steps }), expanded);
+        }
+        // Reached again for cheaper since, already expanded then
+        if current_cost > best[&current].0 {
+            continue;
+        }
+
+        expanded += 1;
+        if expanded >= max_nodes {
+            break;
+        }
+
+        for (next, step_cost) in moves_from(current, chunk_mngr) {
+            if avoid.iter().any(|area| area.contains(next)) {
+                continue;
+            }
+            let next_cost = current_cost + step_cost;
+
+            if best.get(&next).is_none_or(|(c, _)| next_cost < *c) {
+                best.insert(next, (next_cost, current));
+                let h = heuristic(next, goal);
+                open.push(Reverse((next_cost + h, h, next)));
+            }
+        }
+    }
+
+    (None, expanded)
+}
+
+/// Shared by all the ants of a colony : caches paths and limits
+/// how much searching happens each tick
+#[derive(Default)]
+pub struct Pathfinder {
+    cache: HashMap<(Pos, Pos), Path>,
+    used_this_tick: usize,
+    /// Areas the player kept out
+    keep_out: Vec<Area>,
+}
+
+impl Pathfinder {
+    pub fn start_tick(&mut self) {
+        self.used_this_tick = 0;
+    }
+
+    /// The world changed, cached paths may go through walls now
+    pub fn invalidate(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn set_keep_out(&mut self, keep_out: Vec<Area>) {
+        self.keep_out = keep_out;
+        self.invalidate();
+    }
+
+    pub fn keep_out(&self) -> &[Area] {
+        &self.keep_out
+    }
+
+    pub fn find(&mut self, start: Pos, goal: Pos, chunk_mngr: &ChunkManager) -> Search {
+        if let Some(path) = self.cache.get(&(start, goal)) {
+            return Search::Found(path.clone());
+        }
+
+        let budget = TICK_BUDGET.saturating_sub(self.used_this_tick).min(MAX_SEARCH_NODES);
+        if budget == 0 {
+            return Search::Later;
+        }
+
+        let (path, expanded) = find_path(start, goal, chunk_mngr, &self.keep_out, budget);
+        self.used_this_tick += expanded;
+
+        match path {
+            Some(path) => {
+                if self.cache.len() >= MAX_CACHED_PATHS {
+                    self.cache.clear();
+                }
+                self.cache.insert((start, goal), path.clone());
+                Search::Found(path)
+            }
+            // Search was cut short by this tick's budget, not by the max search size
+            None if budget < MAX_SEARCH_NODES && expanded >= budget => Search::Later,
+            None => Search::Unreachable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{
+            apply_gravity,
+            designation::Area,
+            pathfinding::{ find_path, moves_from, Pathfinder, Search },
+        },
+        chunk::{ tile::Tile, ChunkManager, HEIGHT, WIDTH },
+    };
+
+    #[test]
+    fn crosses_chunks_climbs_and_falls() {
+        let mngr = ChunkManager::flat_world();
+        // One tile step on the way
+        for y in 0..WIDTH as i32 {
+            mngr.set_tile((10, y, 2), Tile::DIRT);
+        }
+
+        let (path, _) = find_path((1, 1, 2), (14, 1, 2), &mngr, &[], 1_000);
+        let path = path.expect("No path found");
+
+        assert!(path.steps.contains(&(10, 1, 3)), "Should climb the step");
+        assert_eq!(path.steps.back(), Some(&(14, 1, 2)), "Should fall back to the floor");
+        assert_eq!(path.steps.len(), 13);
+    }
+
+    #[test]
+    fn walls_are_unreachable() {
+        let mngr = ChunkManager::flat_world();
+        // Two tiles high wall
+        for y in 0..WIDTH as i32 {
+            mngr.set_tile((10, y, 2), Tile::DIRT);
+            mngr.set_tile((10, y, 3), Tile::DIRT);
+        }
+
+        let mut pathfinder = Pathfinder::default();
+        assert!(matches!(pathfinder.find((1, 1, 2), (14, 1, 2), &mngr), Search::Unreachable));
+        assert!(matches!(pathfinder.find((1, 1, 2), (8, 1, 2), &mngr), Search::Found(_)));
+
+        // The player says no
+        pathfinder.set_keep_out(vec![Area::new((5, 0, 0), (5, 7, 10))]);
+        assert!(matches!(pathfinder.find((1, 1, 2), (8, 1, 2), &mngr), Search::Unreachable));
+    }
+
+    #[test]
+    fn top_of_the_world() {
+        let mngr = ChunkManager::flat_world();
+        let top = (HEIGHT as i32) - 1;
+        assert_eq!(mngr.tile_at((2, 1, top + 1)), None);
+        assert_eq!(mngr.tile_at((2, 1, -1)), None);
+
+        // Nothing to climb onto above a wall that tall
+        mngr.set_tile((3, 1, top), Tile::DIRT);
+        let moves = moves_from((2, 1, top), &mngr);
+        assert!(moves.iter().all(|(p, _)| p.0 != 3));
+        assert_eq!(apply_gravity(&(2, 1, top), &mngr), (2, 1, 2));
+    }
+}
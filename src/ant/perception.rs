@@ -0,0 +1,396 @@
+use std::{ collections::{ HashMap, VecDeque }, sync::OnceLock };
+
+use crate::{
+    ant::{ brood::Caste, combat::DETECTION_RADIUS, pheromone::Pheromone, Context },
+    chunk::{ tile::{ Tile, TileFlag }, ChunkManager, WIDTH },
+    time::TICKS_PER_MINUTE,
+};
+
+type Pos = (i32, i32, i32);
+
+/// Ants mostly see what's on their own level
+const SIGHT_DEPTH: i32 = 1;
+/// Explorers see the farthest
+const MAX_SIGHT: i32 = 8;
+/// Fainter pheromones go unnoticed
+const SMELL_THRESHOLD: f32 = 0.1;
+
+/// How far a caste sees, in tiles
+pub fn sight(caste: Caste) -> i32 {
+    match caste {
+        Caste::Explorer => MAX_SIGHT,
+        Caste::Soldier => DETECTION_RADIUS,
+        Caste::Worker => 4,
+        Caste::Nurse | Caste::Drone | Caste::Gyne => 3,
+    }
+}
+
+/// What an ant senses around it
+#[derive(Debug, Clone, Default)]
+pub struct Perception {
+    /// Ants of the other colonies, closest first
+    pub enemies: Vec<Pos>,
+    /// Closest first
+    pub food: Vec<Pos>,
+    /// Everything around, in sight or not
+    view: View,
+    /// Whether each tile of the view is in sight
+    in_sight: Vec<bool>,
+}
+
+impl Perception {
+    /// Everything within `radius` of `pos` with nothing solid in between
+    pub fn sense(pos: Pos, radius: i32, ctx: &Context) -> Self {
+        let lines = SightLines::of(radius);
+        let view = View::around(pos, radius, ctx);
+        let mut in_sight = vec![false; view.tiles.len()];
+        let mut food = vec![];
+
+        // Already closest first
+        for SightLine { offset, at, between: (start, end) } in lines.tiles.iter() {
+            let at = *at as usize;
+            let Some(tile) = view.tiles[at] else {
+                continue;
+            };
+            let on_the_way = &lines.between[*start as usize..*end as usize];
+            if !on_the_way.iter().all(|i| view.is_open(*i as usize)) {
+                continue;
+            }
+            in_sight[at] = true;
+
+            if tile.food_quantity() > 0 {
+                food.push((pos.0 + offset.0 as i32, pos.1 + offset.1 as i32, pos.2 + offset.2 as i32));
+            }
+        }
+
+        let distance = |p: &Pos| p.0.abs_diff(pos.0) + p.1.abs_diff(pos.1) + p.2.abs_diff(pos.2);
+        let mut enemies: Vec<Pos> = ctx.enemies
+            .around(pos, radius)
+            .filter(|p| {
+                view.index(*p).is_some() &&
+                    between(pos, *p).all(|p| view.index(p).is_some_and(|i| view.is_open(i)))
+            })
+            .collect();
+        enemies.sort_by_key(distance);
+
+        Self { enemies, food, view, in_sight }
+    }
+
+    pub fn is_in_sight(&self, p: Pos) -> bool {
+        self.view.index(p).is_some_and(|i| self.in_sight[i])
+    }
+
+    /// Tiles in sight, walls included, closest first
+    pub fn tiles(&self) -> impl Iterator<Item = (Pos, Tile)> + '_ {
+        self.lines().filter_map(|(p, at)| self.view.tiles[at].map(|tile| (p, tile)))
+    }
+
+    /// Every pheromone smelled on the tiles in sight, closest first
+    pub fn pheromones(&self) -> impl Iterator<Item = (Pos, Pheromone, f32)> + '_ {
+        self.lines().flat_map(|(p, at)| {
+            Pheromone::ALL
+                .into_iter()
+                .zip(self.view.smells[at])
+                .filter(|(_kind, strength)| *strength > SMELL_THRESHOLD)
+                .map(move |(kind, strength)| (p, kind, strength))
+        })
+    }
+
+    /// Where the tiles in sight are, and where they are in the view
+    fn lines(&self) -> impl Iterator<Item = (Pos, usize)> + '_ {
+        let pos = self.view.pos;
+        // Nothing at all was sensed
+        let lines = if self.in_sight.is_empty() { &[][..] } else { &SightLines::of(self.view.radius).tiles[..] };
+
+        lines
+            .iter()
+            .filter(|line| self.in_sight[line.at as usize])
+            .map(move |SightLine { offset, at, .. }| {
+                ((pos.0 + offset.0 as i32, pos.1 + offset.1 as i32, pos.2 + offset.2 as i32), *at as usize)
+            })
+    }
+}
+
+// Ants see no further than the next chunk, see Senses::forget
+const _: () = assert!(MAX_SIGHT <= WIDTH as i32);
+
+/// What was sensed from each place during a tick, ants standing together see the same things
+/// Kept by chunk, like the Pathfinder's cache it must be cleared when the world changes
+#[derive(Default)]
+pub struct Senses(HashMap<(i32, i32), HashMap<(Pos, i32), Perception>>);
+
+impl Senses {
+    pub fn sense(&mut self, pos: Pos, radius: i32, ctx: &Context) -> &Perception {
+        let (chunk_pos, _) = ChunkManager::to_local(pos);
+        self.0
+            .entry(chunk_pos)
+            .or_default()
+            .entry((pos, radius))
+            .or_insert_with(|| Perception::sense(pos, radius, ctx))
+    }
+
+    /// Tiles changed, what was seen of them too
+    pub fn invalidate(&mut self) {
+        self.0.clear();
+    }
+
+    /// Only the tile at `p` changed, it was only seen from this chunk or the next ones
+    pub fn forget(&mut self, p: Pos) {
+        let ((cx, cy), _) = ChunkManager::to_local(p);
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                self.0.remove(&(x, y));
+            }
+        }
+    }
+}
+
+/// Every tile within a sight radius, and the tiles a line of sight to it goes through
+/// Worked out once for each radius, tiles are then looked up in a View
+struct SightLines {
+    tiles: Vec<SightLine>,
+    /// Where the tiles in between are in the View, see SightLine::between
+    between: Vec<u16>,
+}
+
+/// Kept small, there are hundreds of them to go through for each ant
+struct SightLine {
+    /// From the ant
+    offset: (i8, i8, i8),
+    /// Where it is in the View
+    at: u16,
+    between: (u16, u16),
+}
+
+impl SightLines {
+    fn of(radius: i32) -> &'static SightLines {
+        static LINES: OnceLock<Vec<SightLines>> = OnceLock::new();
+
+        assert!((0..=MAX_SIGHT).contains(&radius), "Nobody sees {} tiles away", radius);
+        let lines = LINES.get_or_init(|| (0..=MAX_SIGHT).map(SightLines::new).collect());
+        &lines[radius as usize]
+    }
+
+    fn new(radius: i32) -> Self {
+        let mut offsets = vec![];
+        for x in -radius..=radius {
+            for y in -radius..=radius {
+                for z in -SIGHT_DEPTH..=SIGHT_DEPTH {
+                    offsets.push((x, y, z));
+                }
+            }
+        }
+        // Stable, things as far away stay in the same order
+        offsets.sort_by_key(|(x, y, z)| x.abs() + y.abs() + z.abs());
+
+        let mut lines = Self { tiles: vec![], between: vec![] };
+        for offset in offsets {
+            let start = lines.between.len();
+            let on_the_way = between((0, 0, 0), offset).map(|p| View::index_in(p, radius) as u16);
+            lines.between.extend(on_the_way);
+            lines.tiles.push(SightLine {
+                offset: (offset.0 as i8, offset.1 as i8, offset.2 as i8),
+                at: View::index_in(offset, radius) as u16,
+                between: (start as u16, lines.between.len() as u16),
+            });
+        }
+        lines
+    }
+}
+
+/// Tiles and pheromones in sight range, read at once instead of tile by tile
+#[derive(Debug, Clone, Default)]
+struct View {
+    pos: Pos,
+    radius: i32,
+    tiles: Vec<Option<Tile>>,
+    /// Whether each tile can be seen through, checked once for all the lines going through it
+    open: Vec<bool>,
+    smells: Vec<[f32; Pheromone::ALL.len()]>,
+}
+
+impl View {
+    fn around(pos: Pos, radius: i32, ctx: &Context) -> Self {
+        let from = (pos.0 - radius, pos.1 - radius, pos.2 - SIGHT_DEPTH);
+        let to = (pos.0 + radius, pos.1 + radius, pos.2 + SIGHT_DEPTH);
+        let tiles = ctx.chunk_mngr.tiles_in(from, to);
+        let open = tiles
+                .iter()
+                .map(|t| t.is_some_and(|t| t.properties.contains(TileFlag::TRAVERSABLE)))
+                .collect();
+        let smells = ctx.pheromones.get_all_in(from, to);
+        Self {
+            pos,
+            radius,
+            open,
+            tiles,
+            smells,
+        }
+    }
+
+    /// Where the tile at `offset` from the center is, laid out like ChunkManager::tiles_in
+    fn index_in((x, y, z): Pos, radius: i32) -> usize {
+        let w = 2 * radius + 1;
+        ((x + radius) + (y + radius) * w + (z + SIGHT_DEPTH) * w * w) as usize
+    }
+
+    fn index(&self, p: Pos) -> Option<usize> {
+        let (x, y, z) = (p.0 - self.pos.0, p.1 - self.pos.1, p.2 - self.pos.2);
+        let in_view = x.abs() <= self.radius && y.abs() <= self.radius && z.abs() <= SIGHT_DEPTH;
+        in_view.then(|| Self::index_in((x, y, z), self.radius))
+    }
+
+    fn is_open(&self, i: usize) -> bool {
+        self.open[i]
+    }
+}
+
+/// Nothing solid between `from` and `to` (both ends excluded)
+/// Tile by tile, what the sight lines work out for a whole View at once
+#[cfg(test)]
+fn line_of_sight(from: Pos, to: Pos, chunk_mngr: &ChunkManager) -> bool {
+    between(from, to).all(|p| {
+        chunk_mngr.tile_at(p).is_some_and(|t| t.properties.contains(TileFlag::TRAVERSABLE))
+    })
+}
+
+/// Tiles on the way from `from` to `to` (both ends excluded)
+/// Same as rounding `d * i / steps` on each axis, counted without dividing
+fn between(from: Pos, to: Pos) -> impl Iterator<Item = Pos> {
+    let d = [to.0 - from.0, to.1 - from.1, to.2 - from.2];
+    let steps = d[0].abs().max(d[1].abs()).max(d[2].abs());
+    // Moved so far on each axis, and how close it is to the next move
+    let mut moved = [0; 3];
+    let mut error = [steps; 3];
+
+    (1..steps).map(move |_| {
+        for axis in 0..3 {
+            error[axis] += 2 * d[axis].abs();
+            if error[axis] >= 2 * steps {
+                error[axis] -= 2 * steps;
+                moved[axis] += d[axis].signum();
+            }
+        }
+        (from.0 + moved[0], from.1 + moved[1], from.2 + moved[2])
+    })
+}
+
+/// What an ant recalls of the last few minutes
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    /// Closest food seen lately, and when
+    pub food: Option<(Pos, u64)>,
+    pub enemy: Option<(Pos, u64)>,
+    /// Places walked since leaving the nest, the latest last
+    pub route: VecDeque<Pos>,
+}
+
+impl Memory {
+    /// Things seen longer ago are forgotten
+    const SPAN: u64 = TICKS_PER_MINUTE * 2;
+    /// Steps of the route home remembered
+    const ROUTE_LENGTH: usize = 32;
+    /// Steps walked back at once when lost
+    const RETRACE_STEPS: usize = 8;
+    /// The route starts over this close to the nest
+    const HOME_RADIUS: i32 = 2;
+
+    pub fn remember(&mut self, pos: Pos, seen: &Perception, ctx: &Context) {
+        let tick = ctx.tick;
+
+        // Forgotten, or in sight but not there anymore
+        let is_gone = |memory: Option<(Pos, u64)>, there: &[Pos]| {
+            memory.is_some_and(|(p, at)| {
+                let in_sight = seen.is_in_sight(p);
+                tick.saturating_sub(at) > Self::SPAN || (in_sight && !there.contains(&p))
+            })
+        };
+        if let Some(food) = seen.food.first() {
+            self.food = Some((*food, tick));
+        } else if is_gone(self.food, &seen.food) {
+            self.food = None;
+        }
+        if let Some(enemy) = seen.enemies.first() {
+            self.enemy = Some((*enemy, tick));
+        } else if is_gone(self.enemy, &seen.enemies) {
+            self.enemy = None;
+        }
+
+        let (nx, ny, _) = ctx.nest;
+        if (pos.0 - nx).abs() <= Self::HOME_RADIUS && (pos.1 - ny).abs() <= Self::HOME_RADIUS {
+            self.route.clear();
+        } else if self.route.back() != Some(&pos) {
+            self.route.push_back(pos);
+            if self.route.len() > Self::ROUTE_LENGTH {
+                self.route.pop_front();
+            }
+        }
+    }
+
+    /// A few steps back on the way home
+    pub fn way_back(&self) -> Option<Pos> {
+        let back = self.route.len().saturating_sub(Self::RETRACE_STEPS + 1);
+        self.route.get(back).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ant::{
+            brood::Caste,
+            perception::{ line_of_sight, Memory, Perception },
+            pheromone::PheromoneField,
+            spatial::SpatialIndex,
+            Context,
+        },
+        chunk::{ tile::Tile, ChunkManager },
+    };
+
+    #[test]
+    fn walls_block_the_view() {
+        let mngr = ChunkManager::flat_world();
+        let pheromones = PheromoneField::default();
+        mngr.set_tile((6, 2, 2), Tile::GRASS);
+        mngr.set_tile((2, 6, 2), Tile::GRASS);
+        // A wall between the ant and the second patch
+        mngr.set_tile((2, 4, 2), Tile::DIRT);
+        mngr.set_tile((2, 4, 3), Tile::DIRT);
+        assert!(!line_of_sight((2, 2, 2), (2, 6, 2), &mngr));
+
+        let mut ctx = Context {
+            chunk_mngr: &mngr,
+            pheromones: &pheromones,
+            nest: (12, 6, 2),
+            storage: (12, 6, 2),
+            food: 0,
+            tick: 0,
+            seed: 0,
+            needed_caste: Caste::Worker,
+            mound: (12, 6, 2),
+            enemies: &SpatialIndex::new([(2, 7, 2), (5, 3, 2), (14, 2, 2)]),
+            rally: None,
+            brood_chamber: (12, 6, 2),
+        };
+        let seen = Perception::sense((2, 2, 2), 4, &ctx);
+        assert_eq!(seen.food, vec![(6, 2, 2)]);
+        assert_eq!(seen.enemies, vec![(5, 3, 2)]);
+        assert!(seen.tiles().any(|(p, _)| p == (2, 4, 2)), "The wall itself is seen");
+
+        let mut memory = Memory::default();
+        memory.remember((2, 2, 2), &seen, &ctx);
+        assert_eq!(memory.food, Some(((6, 2, 2), 0)));
+
+        // Out of sight, not out of mind
+        ctx.tick = 10;
+        memory.remember((2, 2, 2), &Perception::default(), &ctx);
+        assert_eq!(memory.food, Some(((6, 2, 2), 0)));
+
+        // Somebody ate it
+        mngr.set_tile((6, 2, 2), Tile::AIR);
+        memory.remember((6, 2, 2), &Perception::sense((6, 2, 2), 3, &ctx), &ctx);
+        assert_eq!(memory.food, None);
+        assert_eq!(memory.route, vec![(2, 2, 2), (6, 2, 2)]);
+        assert_eq!(memory.way_back(), Some((2, 2, 2)));
+    }
+}
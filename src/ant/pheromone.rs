@@ -0,0 +1,301 @@
+use std::{ collections::HashMap, ops::Range };
+
+use crate::{
+    ant::direction::Direction,
+    chunk::{ index::{ flatten_index_i32, to_xyz }, ChunkContent, ChunkManager, HEIGHT, WIDTH },
+};
+
+/// Pheromones are updated every PHEROMONE_TICKS ticks
+pub const PHEROMONE_TICKS: u64 = 10;
+/// Part of a tile's pheromone that spreads to its neighbours at each update
+const DIFFUSION: f32 = 0.2;
+/// Below this, pheromones are gone
+const EPSILON: f32 = 0.001;
+const MAX_CONCENTRATION: f32 = 100.0;
+
+const KINDS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pheromone {
+    /// Left by ants carrying food
+    Food,
+    /// Left by ants leaving the nest
+    Home,
+    /// Danger !
+    Alarm,
+    /// Left by explorers, so they don't discover the same place twice
+    Exploration,
+}
+
+impl Pheromone {
+    pub const ALL: [Pheromone; KINDS] = [
+        Pheromone::Food,
+        Pheromone::Home,
+        Pheromone::Alarm,
+        Pheromone::Exploration,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Part of the pheromone lost at each update
+    fn evaporation(self) -> f32 {
+        match self {
+            Pheromone::Food => 0.02,
+            Pheromone::Home => 0.01,
+            // Alarms don't last
+            Pheromone::Alarm => 0.2,
+            Pheromone::Exploration => 0.005,
+        }
+    }
+}
+
+// One bit per level, see PheromoneGrid::layers
+const _: () = assert!(HEIGHT <= 64);
+
+/// Every pheromone on every tile of a chunk
+#[derive(Clone)]
+struct PheromoneGrid {
+    values: Vec<[f32; KINDS]>,
+    /// One bit for each level that may have some pheromone on it
+    layers: u64,
+}
+
+impl PheromoneGrid {
+    fn new() -> Self {
+        Self { values: vec![[0.0; KINDS]; ChunkContent::FLAT_SIZE], layers: 0 }
+    }
+
+    fn level(z: usize) -> Range<usize> {
+        z * WIDTH * WIDTH..(z + 1) * WIDTH * WIDTH
+    }
+
+    /// Back to nothing, only where there was something
+    fn clear(&mut self) {
+        for z in (0..HEIGHT).filter(|z| self.layers & (1 << z) != 0) {
+            self.values[Self::level(z)].fill([0.0; KINDS]);
+        }
+        self.layers = 0;
+    }
+}
+
+/// One colony's pheromones, stored by chunk like the world itself
+#[derive(Default, Clone)]
+pub struct PheromoneField {
+    grids: HashMap<(i32, i32), PheromoneGrid>,
+    /// Grids let go by the last updates, written over instead of allocating new ones
+    spare: Vec<PheromoneGrid>,
+}
+
+impl PheromoneField {
+    pub fn get(&self, p: (i32, i32, i32), kind: Pheromone) -> f32 {
+        self.get_all(p)[kind.index()]
+    }
+
+    fn get_all(&self, p: (i32, i32, i32)) -> [f32; KINDS] {
+        if p.2 < 0 || p.2 >= (HEIGHT as i32) {
+            return [0.0; KINDS];
+        }
+        let (chunk_pos, local) = ChunkManager::to_local(p);
+
+        self.grids
+            .get(&chunk_pos)
+            .map_or([0.0; KINDS], |grid| grid.values[flatten_index_i32(local)])
+    }
+
+    /// Every kind on every tile between `from` and `to` (both included), in the order of
+    /// Pheromone::ALL, laid out like ChunkManager::tiles_in
+    pub fn get_all_in(&self, from: (i32, i32, i32), to: (i32, i32, i32)) -> Vec<[f32; KINDS]> {
+        let (w, h) = (to.0 - from.0 + 1, to.1 - from.1 + 1);
+        let mut values = vec![[0.0; KINDS]; (w * h * (to.2 - from.2 + 1)).max(0) as usize];
+        let (first, _) = ChunkManager::to_local(from);
+        let (last, _) = ChunkManager::to_local(to);
+        let width = WIDTH as i32;
+
+        for cx in first.0..=last.0 {
+            for cy in first.1..=last.1 {
+                let Some(grid) = self.grids.get(&(cx, cy)) else {
+                    continue;
+                };
+                let (x_min, x_max) = (from.0.max(cx * width), to.0.min(cx * width + width - 1));
+                let row = (x_max - x_min + 1) as usize;
+                // Empty levels are left as they are
+                let levels = from.2.max(0)..=to.2.min((HEIGHT as i32) - 1);
+                for z in levels.filter(|z| grid.layers & (1 << z) != 0) {
+                    for y in from.1.max(cy * width)..=to.1.min(cy * width + width - 1) {
+                        // A row at once
+                        let i = ((x_min - from.0) + (y - from.1) * w + (z - from.2) * w * h) as usize;
+                        let j = flatten_index_i32((x_min - cx * width, y - cy * width, z));
+                        values[i..i + row].copy_from_slice(&grid.values[j..j + row]);
+                    }
+                }
+            }
+        }
+        values
+    }
+
+    pub fn deposit(&mut self, p: (i32, i32, i32), kind: Pheromone, amount: f32) {
+        if p.2 < 0 || p.2 >= (HEIGHT as i32) {
+            return;
+        }
+        let (chunk_pos, local) = ChunkManager::to_local(p);
+        let spare = &mut self.spare;
+        let grid = self.grids.entry(chunk_pos).or_insert_with(|| Self::fresh(spare));
+        grid.layers |= 1 << p.2;
+        let value = &mut grid.values[flatten_index_i32(local)][kind.index()];
+
+        *value = (*value + amount).min(MAX_CONCENTRATION);
+    }
+
+    /// Direction where this pheromone is the strongest around `p`, if there is any
+    /// (one tile up or down is fine, ants climb and fall)
+    pub fn gradient(&self, p: (i32, i32, i32), kind: Pheromone) -> Option<Direction> {
+        let mut best = (None, self.get(p, kind).max(EPSILON));
+
+        for direction in [Direction::North, Direction::East, Direction::South, Direction::West] {
+            let (x, y, z) = direction.add_to(&p);
+            let value = (z - 1..=z + 1)
+                .map(|z| self.get((x, y, z), kind))
+                .fold(0.0, f32::max);
+
+            if value > best.1 {
+                best = (Some(direction), value);
+            }
+        }
+        best.0
+    }
+
+    pub fn tick(&mut self, tick: u64) {
+        if tick.is_multiple_of(PHEROMONE_TICKS) {
+            self.spread();
+        }
+    }
+
+    /// An empty grid, a spare one if there is any
+    fn fresh(spare: &mut Vec<PheromoneGrid>) -> PheromoneGrid {
+        spare.pop().map_or_else(PheromoneGrid::new, |mut grid| {
+            grid.clear();
+            grid
+        })
+    }
+
+    /// Diffusion (on the horizontal plane, across chunk borders) then evaporation
+    pub fn spread(&mut self) {
+        // Pheromones may leak into the next chunks
+        let around: Vec<(i32, i32)> = self.grids
+            .keys()
+            .flat_map(|(x, y)| [(x - 1, *y), (x + 1, *y), (*x, y - 1), (*x, y + 1)])
+            .collect();
+        for chunk_pos in around {
+            let spare = &mut self.spare;
+            self.grids.entry(chunk_pos).or_insert_with(|| Self::fresh(spare));
+        }
+
+        let mut spread = Vec::with_capacity(self.grids.len());
+
+        for (chunk_pos, grid) in &self.grids {
+            // Written over, what it held before is cleared level by level
+            let mut next = self.spare.pop().unwrap_or_else(PheromoneGrid::new);
+            let stale = std::mem::take(&mut next.layers);
+            let (cx, cy) = *chunk_pos;
+            // Levels empty here and next door stay empty
+            let active = [(cx, cy), (cx - 1, cy), (cx + 1, cy), (cx, cy - 1), (cx, cy + 1)]
+                .iter()
+                .filter_map(|pos| self.grids.get(pos))
+                .fold(0, |active, grid| active | grid.layers);
+
+            for z in 0..HEIGHT {
+                if active & (1 << z) == 0 {
+                    if stale & (1 << z) != 0 {
+                        next.values[PheromoneGrid::level(z)].fill([0.0; KINDS]);
+                    }
+                    continue;
+                }
+
+                for i in PheromoneGrid::level(z) {
+                    let (x, y, z) = to_xyz(i);
+                    let w = WIDTH as i32;
+                    let values = grid.values[i];
+
+                    let mut neighbours = [0.0; KINDS];
+                    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                        let (nx, ny) = (x + dx, y + dy);
+                        // Only look into other chunks on the borders
+                        let around = if nx >= 0 && nx < w && ny >= 0 && ny < w {
+                            grid.values[flatten_index_i32((nx, ny, z))]
+                        } else {
+                            self.get_all((chunk_pos.0 * w + nx, chunk_pos.1 * w + ny, z))
+                        };
+                        for kind in 0..KINDS {
+                            neighbours[kind] += around[kind];
+                        }
+                    }
+
+                    for kind in Pheromone::ALL {
+                        let k = kind.index();
+                        let diffused = values[k] * (1.0 - DIFFUSION) + (neighbours[k] * DIFFUSION) / 4.0;
+                        let v = diffused * (1.0 - kind.evaporation());
+
+                        next.values[i][k] = if v < EPSILON { 0.0 } else { v };
+                        if v >= EPSILON {
+                            next.layers |= 1 << z;
+                        }
+                    }
+                }
+            }
+            spread.push((*chunk_pos, next));
+        }
+
+        // The old grids are the next update's spares
+        for (chunk_pos, next) in spread {
+            let old = if next.layers == 0 {
+                self.spare.push(next);
+                self.grids.remove(&chunk_pos)
+            } else {
+                self.grids.insert(chunk_pos, next)
+            };
+            self.spare.extend(old);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::{ direction::Direction, pheromone::{ Pheromone, PheromoneField } };
+
+    #[test]
+    fn spreads_across_chunks_and_evaporates() {
+        let mut field = PheromoneField::default();
+        // Right on a chunk border
+        let source = (0, 3, 30);
+        field.deposit(source, Pheromone::Food, 10.0);
+
+        field.spread();
+        assert!(field.get((-1, 3, 30), Pheromone::Food) > 0.0, "Should spread to the next chunk");
+        assert!(field.get(source, Pheromone::Food) < 10.0);
+        assert_eq!(field.get(source, Pheromone::Home), 0.0);
+
+        // Ants follow the smell
+        assert!(matches!(field.gradient((-2, 3, 30), Pheromone::Food), Some(Direction::East)));
+
+        // Alarms are the quickest to fade away
+        let mut field = PheromoneField::default();
+        field.deposit(source, Pheromone::Alarm, 10.0);
+        for _ in 0..50 {
+            field.spread();
+        }
+        assert_eq!(field.get(source, Pheromone::Alarm), 0.0, "Should be gone by now");
+        assert!(field.grids.is_empty());
+
+        // Nothing left over in the reused grids
+        let (from, to) = ((-16, -16, 0), (31, 31, 40));
+        for i in 0..30 {
+            field.deposit((i % 12, 3, 10 + i % 3 * 10), Pheromone::Alarm, 10.0);
+            let mut fresh = PheromoneField { grids: field.grids.clone(), spare: vec![] };
+            field.spread();
+            fresh.spread();
+            assert_eq!(field.get_all_in(from, to), fresh.get_all_in(from, to));
+        }
+    }
+}
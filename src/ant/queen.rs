@@ -1,115 +1,195 @@
-#[allow(unused)]
-use std::{ any::Any, time::{ Duration, Instant } };
-
 use sdl2::pixels::Color;
 
 #[allow(unused)]
-use crate::ant::{ colony::Colony, direction::Direction, Action };
+use crate::ant::{
+    brood::{ Brood, Caste },
+    colony::Colony,
+    combat::{ self, Stats },
+    designation::Area,
+    direction::Direction,
+    jobs::AntId,
+    needs::Needs,
+    pathfinding::Path,
+    spatial::SpatialIndex,
+    Action,
+    Context,
+};
 #[allow(unused)]
 use crate::{
-    ant::{ explorer::Explorer, worker::Worker, AntManager, ColonyMember },
+    ant::{ AntManager, ColonyMember },
     chunk::{ Chunk, ChunkManager },
     renderer::Renderer,
+    time::TICKS_PER_MINUTE,
 };
 
-#[derive(Clone, Copy)]
-pub struct Egg {
-    pub hatch: fn(pos: (i32, i32, i32)) -> Box<dyn ColonyMember>,
-}
-impl Egg {}
 
 #[derive(Clone)]
 pub struct Queen {
     pub pos: (i32, i32, i32),
-    pub last_action: Instant,
-    pub eggs: Vec<Egg>,
+    /// Tick it is free to act again
+    pub next_action: u64,
+    pub path: Option<Path>,
+    /// Eggs about to be laid
+    pub eggs: Vec<Caste>,
+    pub needs: Needs,
+    /// Tick the last eggs were laid at
+    pub last_laid: u64,
+    /// The game is lost when she dies
+    pub stats: Stats,
+    /// Tiles a young queen still has to dig for her founding chamber
+    pub founding: Option<u8>,
+    /// No nurse yet (or anymore), she fends for herself and her brood
+    pub claustral: bool,
 }
 
 impl ColonyMember for Queen where Self: Sized {
-    fn last_action(&self) -> Instant {
-        self.last_action
-    }
-    fn reset_last_action(&mut self) {
-        self.last_action = Instant::now();
+    fn id(&self) -> AntId {
+        0
     }
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn next_action(&self) -> u64 {
+        self.next_action
     }
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
+    fn set_next_action(&mut self, tick: u64) {
+        self.next_action = tick;
     }
 
+    fn needs_mut(&mut self) -> Option<&mut Needs> {
+        Some(&mut self.needs)
+    }
+    fn stats_mut(&mut self) -> &mut Stats {
+        &mut self.stats
+    }
+    fn path_mut(&mut self) -> &mut Option<Path> {
+        &mut self.path
+    }
     fn pos(&self) -> (i32, i32, i32) {
         self.pos
     }
     fn set_pos(&mut self, pos: (i32, i32, i32)) {
         self.pos = pos;
     }
-    fn render(&self, renderer: &mut Renderer) {
-        let (x, y, z) = self.pos;
+    fn think(&mut self, ctx: &Context) -> Option<Action> {
+        if let Some(action) = combat::fight_back(self.pos, ctx) {
+            return Some(action);
+        }
+        // Straight down, away from predators
+        if self.founding.is_some() {
+            let (x, y, z) = self.pos;
+            return Some(Action::Dig((x, y, z - 1)));
+        }
+        // Nurses bring her food, she never leaves her chamber
+        if self.needs.is_hungry() {
+            return if self.claustral && ctx.food > 0 { Some(Action::Eat) } else { None };
+        }
 
-        if z > renderer.camera.2 {
-            return;
+        let is_time = ctx.tick >= self.last_laid + Self::BREEDING_TIMER;
+        if self.eggs.is_empty() && is_time && ctx.food >= Self::EGG_COST {
+            self.eggs.push(ctx.needed_caste);
+            self.last_laid = ctx.tick;
         }
-        let (x, y) = renderer.tile_to_screen_coords((x, y));
-        renderer.draw_tile((x, y), Color::YELLOW);
-    }
-    fn think(&mut self) -> Option<Action> {
+
         if self.eggs.len() > 0 {
-            println!(
-                "The queen is giving birth, {:?} new ants arrived in this world !",
-                self.eggs.len()
-            );
-            let newborns = self.breed();
-            Some(Action::Breed(newborns))
+            let eggs = self.breed();
+            Some(Action::Breed(eggs))
         } else {
             None
         }
     }
 
     #[allow(unused)]
-    fn walk(&mut self, chunk_mngr: &ChunkManager, direction: Direction) {
+    fn walk(&mut self, chunk_mngr: &ChunkManager, keep_out: &[Area], direction: Direction) {
         panic!("Why would i go anywhere ?")
     }
 }
 
 impl Queen {
-    pub const BREEDING_TIMER: f32 = 2.0;
-    pub fn breed(&mut self) -> Vec<Box<dyn ColonyMember>> where Self: Sized {
-        let mut newborns = vec![];
+    /// Ticks between two eggs
+    pub const BREEDING_TIMER: u64 = TICKS_PER_MINUTE * 2;
+    /// Food taken from the stockpile for each egg
+    pub const EGG_COST: u32 = 3;
+    /// Queens live for years
+    pub const LIFESPAN: u64 = u64::MAX;
+    /// Digging power of a young queen, weaker than a worker
+    pub const DIG_STRENGTH: u8 = 30;
+    /// Depth of a founding chamber
+    pub const FOUNDING_DEPTH: u8 = 3;
 
-        for egg in self.eggs.clone() {
-            newborns.push((egg.hatch)(self.pos));
-            self.eggs.pop();
+    pub fn new(pos: (i32, i32, i32)) -> Self {
+        Self {
+            pos,
+            next_action: 0,
+            path: None,
+            eggs: vec![],
+            needs: Needs::new(Self::LIFESPAN),
+            last_laid: 0,
+            stats: Stats::QUEEN,
+            founding: None,
+            claustral: false,
         }
-
-        newborns
+    }
+    pub fn breed(&mut self) -> Vec<Brood> where Self: Sized {
+        self.eggs
+            .drain(..)
+            .map(|caste| Brood::egg(self.pos, caste))
+            .collect()
     }
     pub fn new_worker(&mut self) {
-        self.eggs.insert(0, Egg { hatch: Worker::new });
+        self.eggs.insert(0, Caste::Worker);
+    }
+    pub fn render(&self, renderer: &mut Renderer) {
+        let (x, y, z) = self.pos;
+
+        if z > renderer.camera.2 {
+            return;
+        }
+        let (x, y) = renderer.tile_to_screen_coords((x, y));
+        renderer.draw_tile((x, y), Color::YELLOW);
     }
 }
 
 #[test]
 fn queen() {
-    let mut ant_manager = AntManager::new();
     let mut chunk_manager = ChunkManager::empty();
-    let chunk = Chunk::generate((0, 0), &chunk_manager.world_noise);
+    let mut ant_manager = AntManager::new(chunk_manager.seed, Default::default());
+    let chunk = Chunk::generate((0, 0), &chunk_manager.world_noise, &chunk_manager.rivers);
     let chunk = chunk.join().unwrap();
     chunk_manager.loaded_chunks.insert((0, 0), chunk.clone());
 
-    let bok: &mut Colony = &mut ant_manager.colonies[Colony::PLAYER];
+    let bok: &mut Colony = ant_manager.player_mut().unwrap();
+    let ctx = Context {
+        chunk_mngr: &chunk_manager,
+        pheromones: &bok.pheromones,
+        nest: bok.queen.pos,
+        storage: bok.storage(),
+        food: bok.food,
+        tick: 0,
+        seed: 0,
+        needed_caste: bok.needed_caste(),
+        mound: bok.queen.pos,
+        enemies: &SpatialIndex::default(),
+        rally: None,
+        brood_chamber: (2, 2, 2),
+    };
 
-    // println!("{:?}", self.queen.last_action().duration_since(last_tick));
-    if bok.queen.last_action().duration_since(Instant::now()) > Duration::from_millis(1000) {
-        if let Some(action) = bok.queen.think() {
+    if ctx.tick >= bok.queen.next_action() {
+        if let Some(action) = bok.queen.think(&ctx) {
             match action {
-                Action::Walk(_) => {
+                Action::Walk(_) |
+                Action::GoTo(_) |
+                Action::PickUp(_) |
+                Action::Carry(_) |
+                Action::Dig(_) |
+                Action::Build(_) |
+                Action::Drop |
+                Action::Eat |
+                Action::Rest |
+                Action::Feed(_) |
+                Action::Attack(_) => {
                     panic!("Why would the queen go anywhere ?");
                 }
-                Action::Breed(mut newborns) => {
-                    bok.ants.append(&mut newborns);
-                    bok.queen.reset_last_action();
+                Action::Breed(mut eggs) => {
+                    bok.brood.append(&mut eggs);
+                    bok.queen.set_next_action(ctx.tick + Action::Breed(vec![]).duration());
                 }
             }
         }
@@ -117,3 +197,42 @@ fn queen() {
         panic!("The queen should have something to do !");
     }
 }
+
+#[test]
+fn queen_lays_eggs_with_food() {
+    let chunk_mngr = ChunkManager::flat_world();
+    let mut colony = Colony::new("Test", (2, 2, 2));
+    colony.food = Queen::EGG_COST;
+    let pheromones = colony.pheromones.clone();
+    let mut ctx = Context {
+        chunk_mngr: &chunk_mngr,
+        pheromones: &pheromones,
+        nest: colony.queen.pos,
+        storage: colony.storage(),
+        food: colony.food,
+        tick: Queen::BREEDING_TIMER,
+        seed: 0,
+        needed_caste: colony.needed_caste(),
+        mound: colony.queen.pos,
+        enemies: &SpatialIndex::default(),
+        rally: None,
+        brood_chamber: (2, 2, 2),
+    };
+
+    match colony.queen.think(&ctx) {
+        Some(Action::Breed(eggs)) => assert_eq!(eggs[0].caste, Caste::Worker),
+        _ => panic!("The queen should lay an egg"),
+    }
+    // Not yet
+    ctx.tick += 1;
+    assert!(colony.queen.think(&ctx).is_none());
+
+    // No food, no eggs
+    ctx.tick += Queen::BREEDING_TIMER;
+    ctx.food = 0;
+    assert!(colony.queen.think(&ctx).is_none());
+
+    colony.queen.needs.satiety = 0.0;
+    ctx.food = 10;
+    assert!(colony.queen.think(&ctx).is_none(), "Should wait for the nurses");
+}
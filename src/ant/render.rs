@@ -1,15 +1,77 @@
+use sdl2::pixels::Color;
+
 use crate::{
-    ant::{ colony::Colony, ColonyMember },
+    ant::{ behavior::Task, colony::Colony, explored::ExploredMap },
     chunk::tile::{ Tile, TileFlag },
     renderer::Renderer,
 };
 
+/// Unexplored tiles, from the player's point of view
+const FOG_COLOR: Color = Color::RGB(10, 10, 15);
+
 impl Renderer<'_> {
     pub fn draw_ants(&mut self, colony: &Colony, timestamp: f64) {
-        colony.queen.clone().render(self);
+        for brood in colony.brood.iter() {
+            brood.render(self);
+        }
+        colony.queen.render(self);
+        self.outline(colony.queen.pos, colony.color);
+
+        let ants = colony.ants.positions().iter().zip(colony.ants.castes());
+        for (pos, caste) in ants {
+            self.draw_ant(*pos, caste.color());
+            self.outline(*pos, colony.color);
+        }
+
+        if self.is_behavior_shown {
+            for (pos, node) in colony.ants.positions().iter().zip(colony.ants.nodes()) {
+                self.behavior_label(*pos, *node);
+            }
+        }
+    }
+
+    fn draw_ant(&mut self, (x, y, z): (i32, i32, i32), color: Color) {
+        if z > self.camera.2 {
+            return;
+        }
+        let (x, y) = self.tile_to_screen_coords((x, y));
+        self.draw_tile((x, y), color);
+    }
+
+    /// Task of the ant's behavior tree, next to it
+    fn behavior_label(&mut self, (x, y, z): (i32, i32, i32), node: Option<Task>) {
+        let Some(node) = node else {
+            return;
+        };
+        if z > self.camera.2 {
+            return;
+        }
+        let (x, y) = self.tile_to_screen_coords((x, y));
+        self.draw_text(&format!("{:?}", node), x + self.tile_size as i32, y);
+    }
+
+    /// Hides the world a colony hasn't explored yet
+    pub fn draw_fog(&mut self, explored: &ExploredMap) {
+        let (w, h) = self.dims;
+        let (x_min, y_min) = self.screen_to_tile_coords((0, 0));
+        let (x_max, y_max) = self.screen_to_tile_coords((w as i32, h as i32));
+
+        for x in x_min - 1..=x_max + 1 {
+            for y in y_min - 1..=y_max + 1 {
+                if !explored.is_explored((x, y)) {
+                    let pos = self.tile_to_screen_coords((x, y));
+                    self.fill_rect(pos, FOG_COLOR);
+                }
+            }
+        }
+    }
 
-        for ant in colony.ants.iter() {
-            ant.render(self);
+    /// Tells which colony an ant belongs to
+    fn outline(&mut self, (x, y, z): (i32, i32, i32), color: Color) {
+        if z > self.camera.2 {
+            return;
         }
+        let (x, y) = self.tile_to_screen_coords((x, y));
+        self.rect((x, y), color);
     }
 }
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use crate::chunk::ChunkManager;
+
+type Pos = (i32, i32, i32);
+
+/// Positions sorted by chunk, so looking around doesn't go through every ant of the world
+/// Chunks are kept in order, the same positions are always found in the same order
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    chunks: BTreeMap<(i32, i32), Vec<Pos>>,
+}
+
+impl SpatialIndex {
+    pub fn new(positions: impl IntoIterator<Item = Pos>) -> Self {
+        let mut index = Self::default();
+        for pos in positions {
+            index.insert(pos);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, pos: Pos) {
+        let (chunk_pos, _) = ChunkManager::to_local(pos);
+        self.chunks.entry(chunk_pos).or_default().push(pos);
+    }
+
+    /// Everything within `radius` columns of `pos`, whatever the height
+    pub fn around(&self, (x, y, _): Pos, radius: i32) -> impl Iterator<Item = Pos> + '_ {
+        let (from, _) = ChunkManager::to_local((x - radius, y - radius, 0));
+        let (to, _) = ChunkManager::to_local((x + radius, y + radius, 0));
+
+        (from.0..=to.0)
+            .flat_map(move |cx| (from.1..=to.1).map(move |cy| (cx, cy)))
+            .filter_map(|chunk_pos| self.chunks.get(&chunk_pos))
+            .flatten()
+            .copied()
+            .filter(move |p| (p.0 - x).abs() <= radius && (p.1 - y).abs() <= radius)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.chunks.values().flatten().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::spatial::SpatialIndex;
+
+    #[test]
+    fn neighbors_across_chunks() {
+        let index = SpatialIndex::new([(0, 0, 2), (-1, 3, 2), (5, 5, 9), (40, 0, 2)]);
+        assert_eq!(index.len(), 4);
+
+        let mut around: Vec<_> = index.around((1, 1, 2), 4).collect();
+        around.sort();
+        assert_eq!(around, vec![(-1, 3, 2), (0, 0, 2), (5, 5, 9)]);
+        assert_eq!(index.around((1, 1, 2), 1).collect::<Vec<_>>(), vec![(0, 0, 2)]);
+        assert_eq!(index.around((20, 0, 2), 3).count(), 0);
+    }
+}
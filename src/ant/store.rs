@@ -0,0 +1,259 @@
+use crate::ant::{
+    behavior::Task,
+    brood::Caste,
+    combat::Stats,
+    jobs::{ AntId, Job },
+    needs::Needs,
+    pathfinding::Path,
+    perception::Memory,
+    pheromone::Pheromone,
+    Cargo,
+    ColonyMember,
+};
+
+type Pos = (i32, i32, i32);
+
+/// Every component of a single ant, see Ants for where they are kept
+#[derive(Debug, Clone)]
+pub struct Ant {
+    pub id: AntId,
+    pub caste: Caste,
+    pub pos: Pos,
+    /// Tick it is free to act again
+    pub next_action: u64,
+    pub path: Option<Path>,
+    pub needs: Needs,
+    pub stats: Stats,
+    /// Job taken from the colony board, alates never work
+    pub job: Option<Job>,
+    /// Only workers and nurses carry things
+    pub cargo: Option<Cargo>,
+    /// Alates forget everything
+    pub memory: Memory,
+    /// Last task of its behavior tree
+    pub node: Option<Task>,
+}
+
+impl Ant {
+    /// Newborn, the colony gives it an id when it joins
+    pub fn new(caste: Caste, pos: Pos, lifespan: u64) -> Self {
+        Self {
+            id: 0,
+            caste,
+            pos,
+            next_action: 0,
+            path: None,
+            needs: Needs::new(lifespan),
+            stats: Stats::of(caste),
+            job: None,
+            cargo: None,
+            memory: Memory::default(),
+            node: None,
+        }
+    }
+
+    /// Borrows it like an ant of a colony, to think and act
+    pub fn as_mut(&mut self) -> AntMut<'_> {
+        AntMut {
+            id: self.id,
+            caste: self.caste,
+            pos: &mut self.pos,
+            next_action: &mut self.next_action,
+            path: &mut self.path,
+            needs: &mut self.needs,
+            stats: &mut self.stats,
+            job: &mut self.job,
+            cargo: &mut self.cargo,
+            memory: &mut self.memory,
+            node: &mut self.node,
+        }
+    }
+}
+
+/// One ant, borrowed from wherever it is kept
+pub struct AntMut<'a> {
+    id: AntId,
+    caste: Caste,
+    pos: &'a mut Pos,
+    next_action: &'a mut u64,
+    path: &'a mut Option<Path>,
+    needs: &'a mut Needs,
+    stats: &'a mut Stats,
+    job: &'a mut Option<Job>,
+    cargo: &'a mut Option<Cargo>,
+    memory: &'a mut Memory,
+    node: &'a mut Option<Task>,
+}
+
+impl ColonyMember for AntMut<'_> {
+    fn id(&self) -> AntId {
+        self.id
+    }
+    fn caste(&self) -> Option<Caste> {
+        Some(self.caste)
+    }
+    fn pos(&self) -> Pos {
+        *self.pos
+    }
+    fn set_pos(&mut self, pos: Pos) {
+        *self.pos = pos;
+    }
+    fn next_action(&self) -> u64 {
+        *self.next_action
+    }
+    fn set_next_action(&mut self, tick: u64) {
+        *self.next_action = tick;
+    }
+    fn path_mut(&mut self) -> &mut Option<Path> {
+        self.path
+    }
+    fn needs_mut(&mut self) -> Option<&mut Needs> {
+        Some(self.needs)
+    }
+    fn stats_mut(&mut self) -> &mut Stats {
+        self.stats
+    }
+    fn job_mut(&mut self) -> Option<&mut Option<Job>> {
+        (!self.caste.is_alate()).then_some(self.job)
+    }
+    fn cargo_mut(&mut self) -> Option<&mut Option<Cargo>> {
+        self.caste.can_carry().then_some(self.cargo)
+    }
+    fn memory_mut(&mut self) -> Option<&mut Memory> {
+        (!self.caste.is_alate()).then_some(self.memory)
+    }
+    fn set_node(&mut self, node: Option<Task>) {
+        *self.node = node;
+    }
+    fn trail(&self) -> Option<Pheromone> {
+        match self.caste {
+            Caste::Worker => Some(match self.cargo {
+                // Tells the others where the food is
+                Some(Cargo::Food(_)) => Pheromone::Food,
+                Some(Cargo::Spoil(_) | Cargo::Brood(_)) | None => Pheromone::Home,
+            }),
+            Caste::Explorer => Some(Pheromone::Exploration),
+            _ => None,
+        }
+    }
+}
+
+/// Ants of a colony, one column per component, so a tick goes through each of them in a row
+#[derive(Debug, Clone, Default)]
+pub struct Ants {
+    ids: Vec<AntId>,
+    castes: Vec<Caste>,
+    pos: Vec<Pos>,
+    next_action: Vec<u64>,
+    paths: Vec<Option<Path>>,
+    needs: Vec<Needs>,
+    stats: Vec<Stats>,
+    jobs: Vec<Option<Job>>,
+    cargo: Vec<Option<Cargo>>,
+    memory: Vec<Memory>,
+    nodes: Vec<Option<Task>>,
+}
+
+impl Ants {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn push(&mut self, ant: Ant) {
+        self.ids.push(ant.id);
+        self.castes.push(ant.caste);
+        self.pos.push(ant.pos);
+        self.next_action.push(ant.next_action);
+        self.paths.push(ant.path);
+        self.needs.push(ant.needs);
+        self.stats.push(ant.stats);
+        self.jobs.push(ant.job);
+        self.cargo.push(ant.cargo);
+        self.memory.push(ant.memory);
+        self.nodes.push(ant.node);
+    }
+
+    /// The `i`th ant, panics if there is none
+    pub fn get_mut(&mut self, i: usize) -> AntMut<'_> {
+        AntMut {
+            id: self.ids[i],
+            caste: self.castes[i],
+            pos: &mut self.pos[i],
+            next_action: &mut self.next_action[i],
+            path: &mut self.paths[i],
+            needs: &mut self.needs[i],
+            stats: &mut self.stats[i],
+            job: &mut self.jobs[i],
+            cargo: &mut self.cargo[i],
+            memory: &mut self.memory[i],
+            node: &mut self.nodes[i],
+        }
+    }
+
+    /// Keeps the ants `keep` is true for, in the same order
+    pub fn retain(&mut self, mut keep: impl FnMut(AntMut) -> bool) {
+        let kept: Vec<bool> = (0..self.len()).map(|i| keep(self.get_mut(i))).collect();
+        if kept.iter().all(|kept| *kept) {
+            return;
+        }
+
+        fn retain<T>(column: &mut Vec<T>, kept: &[bool]) {
+            let mut kept = kept.iter();
+            column.retain(|_| *kept.next().unwrap());
+        }
+        retain(&mut self.ids, &kept);
+        retain(&mut self.castes, &kept);
+        retain(&mut self.pos, &kept);
+        retain(&mut self.next_action, &kept);
+        retain(&mut self.paths, &kept);
+        retain(&mut self.needs, &kept);
+        retain(&mut self.stats, &kept);
+        retain(&mut self.jobs, &kept);
+        retain(&mut self.cargo, &kept);
+        retain(&mut self.memory, &kept);
+        retain(&mut self.nodes, &kept);
+    }
+
+    pub fn castes(&self) -> &[Caste] {
+        &self.castes
+    }
+
+    pub fn positions(&self) -> &[Pos] {
+        &self.pos
+    }
+
+    /// Tasks of their behavior trees, for the debug view
+    pub fn nodes(&self) -> &[Option<Task>] {
+        &self.nodes
+    }
+
+    pub fn stats_mut(&mut self) -> &mut [Stats] {
+        &mut self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ant::{ brood::Caste, store::Ants, ColonyMember };
+
+    #[test]
+    fn columns_stay_aligned() {
+        let mut ants = Ants::default();
+        for x in 0..4 {
+            let caste = if x % 2 == 0 { Caste::Worker } else { Caste::Explorer };
+            ants.push(caste.hatch((x, 0, 2)));
+        }
+        ants.get_mut(2).set_pos((2, 5, 2));
+
+        // The first two go away
+        ants.retain(|ant| ant.pos().0 >= 2);
+        assert_eq!(ants.len(), 2);
+        assert_eq!(ants.positions(), &[(2, 5, 2), (3, 0, 2)]);
+        assert!(ants.get_mut(0).cargo_mut().is_some(), "Workers carry");
+        assert!(ants.get_mut(1).cargo_mut().is_none(), "Explorers don't");
+    }
+}
@@ -0,0 +1,107 @@
+//! Generation benchmarks, run them with
+//! `cargo test --release bench -- --ignored --nocapture`
+use std::{ hint::black_box, time::{ Duration, Instant } };
+
+use crate::chunk::{
+    biomes::Biome,
+    generation::Column,
+    hydrology::Rivers,
+    manager::LoadedChunk,
+    Chunk,
+    ChunkManager,
+    HEIGHT,
+    WIDTH,
+};
+
+const SAMPLES: i32 = 10_000;
+const CHUNKS: i32 = 8;
+
+fn bench(label: &str, iterations: i32, mut f: impl FnMut(i32)) -> Duration {
+    let start = Instant::now();
+    for i in 0..iterations {
+        f(i);
+    }
+    let per_iter = start.elapsed() / (iterations as u32);
+
+    println!("{:<32} {:>12.2?}", label, per_iter);
+    per_iter
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn bench_layers() {
+    let mngr = ChunkManager::empty();
+    let noise = &mngr.world_noise;
+
+    bench("biome params (4 layers)", SAMPLES, |i| {
+        black_box(Biome::get_biome_params(i as f64, -i as f64, noise));
+    });
+    bench("surface height (3 layers)", SAMPLES, |i| {
+        black_box(Chunk::surface_height((i as f64, -i as f64, 10.0), noise));
+    });
+    bench("column sample", SAMPLES, |i| {
+        black_box(Column::sample((i, -i), noise));
+    });
+
+    let column = Column::sample((0, 0), noise);
+    bench("underground tile (caves)", SAMPLES, |i| {
+        black_box(column.tile(1 + (i % 10), noise));
+    });
+    bench("column of tiles", SAMPLES / 10, |_| {
+        for z in 0..HEIGHT as i32 {
+            black_box(column.tile(z, noise));
+        }
+    });
+
+    bench("terrain (per chunk)", CHUNKS, |i| {
+        black_box(Chunk::generate_terrain((i, i), noise));
+    });
+    bench("rivers and lakes (per chunk)", CHUNKS, |i| {
+        black_box(Chunk::water_columns((i, i), noise, &Rivers::default()));
+    });
+    // Next to each other, like the game loads them
+    bench("rivers and lakes, shared", CHUNKS, |i| {
+        black_box(Chunk::water_columns((i, 0), noise, &mngr.rivers));
+    });
+    let (terrain, columns) = Chunk::generate_terrain((0, 0), noise);
+    bench("vegetation (per chunk)", CHUNKS, |_| {
+        let mut chunk = Chunk { content: terrain.content };
+        chunk.grow_vegetation(black_box(&columns));
+    });
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn bench_chunks_per_second() {
+    let mngr = ChunkManager::empty();
+    let noise = &mngr.world_noise;
+
+    // How it used to be done : every voxel samples every layer again,
+    // and the chunk is locked for each tile
+    let per_voxel = bench("terrain, per voxel", CHUNKS, |i| {
+        let chunk = LoadedChunk::new((i, 0));
+        for z in 0..HEIGHT as i32 {
+            for x in 0..WIDTH as i32 {
+                for y in 0..WIDTH as i32 {
+                    let p = (i * (WIDTH as i32) + x, y, z);
+                    chunk.c.lock().unwrap().set((x, y, z), Chunk::tile_at(p, noise));
+                }
+            }
+        }
+    });
+    let per_column = bench("terrain, per column", CHUNKS, |i| {
+        black_box(Chunk::generate_terrain((i, 0), noise));
+    });
+    println!("Speedup : x{:.1}", per_voxel.as_secs_f64() / per_column.as_secs_f64());
+
+    // Full generation, one thread per chunk like the game does
+    let start = Instant::now();
+    let handles = mngr.generate_range(-CHUNKS..CHUNKS, -CHUNKS..CHUNKS);
+    let count = handles.len();
+    for h in handles {
+        black_box(h.join().unwrap());
+    }
+    println!("{:.1} chunks/sec", (count as f64) / start.elapsed().as_secs_f64());
+
+    assert!(per_column < per_voxel);
+}
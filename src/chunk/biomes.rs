@@ -2,7 +2,7 @@ use std::{ collections::HashMap, fmt::Debug, sync::{mpsc, Arc} };
 
 use noise::{ Fbm, NoiseFn, Perlin };
 
-use crate::chunk::{ manager::WorldNoise, ChunkManager as Manager };
+use crate::chunk::{ hydrology::Rivers, manager::WorldNoise, ChunkManager as Manager };
 
 #[derive(Debug, Clone, Copy)]
 pub struct Biome {
@@ -28,6 +28,30 @@ impl Biome {
 
         biome_factor * roughness_effect
     }
+
+    /// Rough climate classification, used for vegetation
+    pub fn kind(&self) -> BiomeKind {
+        if self.temperature < -0.3 {
+            BiomeKind::Tundra
+        } else if self.humidity > 0.3 {
+            BiomeKind::Swamp
+        } else if self.temperature > 0.3 && self.humidity < -0.1 {
+            BiomeKind::Desert
+        } else if self.humidity > 0.0 {
+            BiomeKind::Forest
+        } else {
+            BiomeKind::Grassland
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BiomeKind {
+    Desert,
+    Grassland,
+    Forest,
+    Swamp,
+    Tundra,
 }
 
 #[derive(Debug, Clone)]
@@ -83,12 +107,10 @@ impl Default for NoiseParams {
 // Hard coded biome parameters
 impl Biome {
     pub fn get_biome_params(x: f64, y: f64, world_noise: &WorldNoise) -> Self {
-        let scale = world_noise[Manager::SURFACE].scale; // grande échelle = variation lente
-
-        let humidity = world_noise[Manager::HUMIDITY].get((x * scale, y * scale, 0.0));
-        let temperature = world_noise[Manager::TEMPERATURE].get((x * scale, y * scale, 5.0));
-        let elevation = world_noise[Manager::ELEVATION].get((x * scale, y * scale, 15.0));
-        let roughness = world_noise[Manager::ROUGHNESS].get((x * scale, y * scale, 20.0));
+        let humidity = Manager::sample_layer(world_noise, Manager::HUMIDITY, (x, y, 0.0));
+        let temperature = Manager::sample_layer(world_noise, Manager::TEMPERATURE, (x, y, 0.0));
+        let elevation = Manager::sample_layer(world_noise, Manager::ELEVATION, (x, y, 0.0));
+        let roughness = Manager::sample_layer(world_noise, Manager::ROUGHNESS, (x, y, 0.0));
 
         let min = -1.0;
         let max = 1.0;
@@ -113,13 +135,52 @@ impl Manager {
     pub const ROUGHNESS: usize = 8;
     pub const TEMPERATURE: usize = 9;
 
+    /// File friendly names of the world noise layers, in index order
+    pub const LAYER_NAMES: [&'static str; 10] = [
+        "surface",
+        "variations",
+        "details",
+        "caves",
+        "tunnels",
+        "veins",
+        "humidity",
+        "elevation",
+        "roughness",
+        "temperature",
+    ];
+
+    /// A noise layer at (x, y, z), sampled the way the generation does
+    /// Every layer but the surface is stretched by the surface scale, biome layers are flat
+    pub fn sample_layer(world_noise: &WorldNoise, layer: usize, (x, y, z): (f64, f64, f64)) -> f64 {
+        let scale = world_noise[Self::SURFACE].scale; // grande échelle = variation lente
+        let at = match layer {
+            Self::SURFACE => (x, y, z),
+            Self::HUMIDITY => (x * scale, y * scale, 0.0),
+            Self::TEMPERATURE => (x * scale, y * scale, 5.0),
+            Self::ELEVATION => (x * scale, y * scale, 15.0),
+            Self::ROUGHNESS => (x * scale, y * scale, 20.0),
+            _ => (x * scale, y * scale, z * scale),
+        };
+        world_noise[layer].get(at)
+    }
+
+    /// Seed of the world everybody knows
+    pub const DEFAULT_SEED: u32 = 0;
+
     pub fn empty() -> Self {
+        Self::with_seed(Self::DEFAULT_SEED)
+    }
+
+    /// Empty manager whose noise layers are all derived from `seed`
+    pub fn with_seed(seed: u32) -> Self {
+        let seeded = |offset: u32| Fbm::new(seed.wrapping_add(offset));
         let (rx, tx) = mpsc::channel();
         Self {
+            seed,
             world_noise: Arc::new([
                 // Surface
                 NoiseParams {
-                    fbm: Fbm::new(1),
+                    fbm: seeded(1),
                     octaves: 5,
                     frequency: 0.5,
                     lacunarity: 2.0,
@@ -128,7 +189,7 @@ impl Manager {
                 },
                 // Variations
                 NoiseParams {
-                    fbm: Fbm::new(1),
+                    fbm: seeded(1),
                     octaves: 1,
                     frequency: 1.0,
                     lacunarity: 2.0,
@@ -137,7 +198,7 @@ impl Manager {
                 },
                 // Details
                 NoiseParams {
-                    fbm: Fbm::new(1),
+                    fbm: seeded(1),
                     octaves: 4,
                     frequency: 1.0,
                     lacunarity: 2.0,
@@ -146,7 +207,7 @@ impl Manager {
                 },
                 // Caves
                 NoiseParams {
-                    fbm: Fbm::new(64),
+                    fbm: seeded(64),
                     octaves: 4,
                     frequency: 1.4,
                     lacunarity: 2.0,
@@ -155,7 +216,7 @@ impl Manager {
                 },
                 // Tunnels
                 NoiseParams {
-                    fbm: Fbm::new(65),
+                    fbm: seeded(65),
                     octaves: 1,
                     frequency: 1.0,
                     lacunarity: 2.0,
@@ -164,7 +225,7 @@ impl Manager {
                 },
                 // Layers
                 NoiseParams {
-                    fbm: Fbm::new(3),
+                    fbm: seeded(3),
                     octaves: 4,
                     frequency: 0.02,
                     lacunarity: 2.0,
@@ -172,10 +233,10 @@ impl Manager {
                     scale: 0.09,
                 },
                 // HUMIDITY
-                NoiseParams::default(),
+                NoiseParams { fbm: seeded(42), ..Default::default() },
                 // ELEVATION
                 NoiseParams {
-                    fbm: Fbm::new(333),
+                    fbm: seeded(333),
                     octaves: 3,
                     frequency: 1.1,
                     lacunarity: 2.0,
@@ -183,10 +244,10 @@ impl Manager {
                     scale: 0.0001,
                 },
                 // ROUGHNESS
-                NoiseParams::default(),
+                NoiseParams { fbm: seeded(42), ..Default::default() },
                 // TEMPERATURE
                 NoiseParams {
-                    fbm: Fbm::new(33),
+                    fbm: seeded(33),
                     octaves: 3,
                     frequency: 1.2,
                     lacunarity: 2.0,
@@ -194,6 +255,7 @@ impl Manager {
                     scale: 0.001,
                 },
             ]),
+            rivers: Rivers::default(),
             rx,
             tx,
             pending_chunks: vec![],
@@ -1,9 +1,9 @@
-use std::sync::Arc;
+use std::sync::{ Arc, Mutex };
 #[allow(unused)]
 use std::{ ops::Range, thread::{ self, JoinHandle }, time::Duration };
 
 use crate::{
-    chunk::{ biomes::Biome, manager::WorldNoise, ChunkContent, SEA_LEVEL },
+    chunk::{ biomes::Biome, hydrology::Rivers, manager::WorldNoise, ChunkContent, SEA_LEVEL },
     renderer::Renderer,
 };
 
@@ -25,6 +25,22 @@ pub const STARTING_MAP_SHAPE: MapShape = if cfg!(test) {
     MapShape::SQUARE
 };
 
+/// Deterministic pseudo random value in 0.0..1.0 for a world column
+/// (same column and salt always give the same value)
+pub fn column_hash((x, y): (i32, i32), salt: u64) -> f64 {
+    let mut h =
+        (x as i64 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^
+        (y as i64 as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f) ^
+        salt.wrapping_mul(0x1656_67b1_9e37_79f9);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^= h >> 33;
+
+    ((h >> 11) as f64) / ((1u64 << 53) as f64)
+}
+
 impl Manager {
     pub fn look_for_new_chunks(&mut self, renderer: &mut Renderer) {
         let (x_min, x_max, y_min, y_max) = renderer.camera_range_i32();
@@ -33,7 +49,7 @@ impl Manager {
         for x in x_min..x_max {
             for y in y_min..y_max {
                 if !self.loaded_chunks.contains_key(&(x, y)) {
-                    new_chunks.push(Chunk::generate((x, y), &self.world_noise));
+                    new_chunks.push(Chunk::generate((x, y), &self.world_noise, &self.rivers));
                 }
             }
         }
@@ -52,7 +68,7 @@ impl Manager {
 
         for j in y_range {
             for i in x_range.clone() {
-                m.push(Chunk::generate((i, j), &self.world_noise));
+                m.push(Chunk::generate((i, j), &self.world_noise, &self.rivers));
             }
         }
 
@@ -65,36 +81,91 @@ impl Chunk {
     }
 }
 
+/// Everything that only depends on (x, y), sampled once per column
+/// The surface height varies with z too, it's still sampled per tile
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub pos: (i32, i32),
+    pub biome: Biome,
+}
+
+impl Column {
+    pub fn sample((x, y): (i32, i32), world_noise: &WorldNoise) -> Self {
+        let (fx, fy) = (x as f64, y as f64);
+
+        Self {
+            pos: (x, y),
+            biome: Biome::get_biome_params(fx, fy, world_noise),
+        }
+    }
+
+    pub fn tile(&self, z: i32, world_noise: &WorldNoise) -> Tile {
+        if z == 0 {
+            return Tile::BEDROCK;
+        }
+
+        let (x, y, z) = (self.pos.0 as f64, self.pos.1 as f64, z as f64);
+        let surface_height = Chunk::surface_height((x, y, z), world_noise);
+        let surface = Chunk::surface(surface_height, (x, y, z), &self.biome);
+
+        // Nothing to dig in the air or the sea, no need for cave noise
+        if z > surface_height && (surface == Tile::AIR || surface == Tile::WATER) {
+            return surface;
+        }
+
+        // let ores = Self::ores((x, y, z), world_noise);
+        let cave = Chunk::cave_noise((x, y, z), world_noise, &self.biome);
+
+        if z <= surface_height || (cave == Tile::AIR && surface != Tile::WATER) {
+            cave
+        } else {
+            surface
+        }
+    }
+}
+
 impl Chunk {
-    pub fn generate(pos: (i32, i32), world_noise: &WorldNoise) -> JoinHandle<LoadedChunk> {
+    pub fn generate(pos: (i32, i32), world_noise: &WorldNoise, rivers: &Rivers) -> JoinHandle<LoadedChunk> {
         // Get the thread safe noise reference
         let world_noise = Arc::clone(&world_noise);
+        let rivers = rivers.clone();
 
         thread::spawn(move || {
-            let chunk = LoadedChunk::new(pos);
-
-            for z in 0..HEIGHT as i32 {
-                for x in 0..WIDTH as i32 {
-                    for y in 0..WIDTH as i32 {
-                        let (nx, ny) = (
-                            (x as f64) + (pos.0 as f64) * (WIDTH as f64),
-                            (y as f64) + (pos.1 as f64) * (WIDTH as f64),
-                        );
-
-                        // Set the tile
-                        let tile = Self::tile_at((nx as i32, ny as i32, z), &world_noise);
-                        chunk.c.lock().unwrap().set((x, y, z), tile);
-                    }
-                }
-            }
+            let (mut chunk, columns) = Self::generate_terrain(pos, &world_noise);
+
+            chunk.carve_water(pos, &world_noise, &rivers);
+            chunk.grow_vegetation(&columns);
+
             //////////////////////////////////////////////////////
             // #[cfg(test)]
             // thread::sleep(Duration::from_millis(150));
             //////////////////////////////////////////////////////
 
-            chunk
+            LoadedChunk { pos, c: Arc::new(Mutex::new(chunk)) }
         })
     }
+
+    /// Fills a local chunk (no locking), sampling 2D layers once per column
+    pub fn generate_terrain(pos: (i32, i32), world_noise: &WorldNoise) -> (Chunk, Vec<Column>) {
+        let mut chunk = Chunk::new();
+        let mut columns = Vec::with_capacity(WIDTH * WIDTH);
+
+        for x in 0..WIDTH as i32 {
+            for y in 0..WIDTH as i32 {
+                let column = Column::sample(
+                    (pos.0 * (WIDTH as i32) + x, pos.1 * (WIDTH as i32) + y),
+                    world_noise
+                );
+
+                for z in 0..HEIGHT as i32 {
+                    chunk.set((x, y, z), column.tile(z, world_noise));
+                }
+                columns.push(column);
+            }
+        }
+
+        (chunk, columns)
+    }
 }
 
 impl Chunk {
@@ -121,12 +192,10 @@ impl Chunk {
     }
 
     fn cave_noise((x, y, z): (f64, f64, f64), world_noise: &WorldNoise, biome: &Biome) -> Tile {
-        let scale = world_noise[Manager::SURFACE].scale;
-
         let cave =
-            world_noise[Manager::CAVES].get((x * scale, y * scale, z * scale)) -
+            Manager::sample_layer(world_noise, Manager::CAVES, (x, y, z)) -
             (z / (HEIGHT as f64)).powf(1.5);
-        let tunnel = world_noise[Manager::TUNNELS].get((x * scale, y * scale, z * scale));
+        let tunnel = Manager::sample_layer(world_noise, Manager::TUNNELS, (x, y, z));
 
         if cave > 0.3 {
             Tile::MARBLE
@@ -143,42 +212,21 @@ impl Chunk {
             Tile::DIRT
         }
     }
-    pub fn tile_at((x, y, z): (i32, i32, i32), world_noise: &WorldNoise) -> Tile {
-        let (x, y, z) = (x as f64, y as f64, z as f64);
-
-        if z == 0.0 {
-            return Tile::BEDROCK;
-        }
-
-        let biome = Biome::get_biome_params(x, y, world_noise);
-
-        let scale = world_noise[Manager::SURFACE].scale;
-        let surface_noise = world_noise[Manager::SURFACE].get((x, y, z));
+    /// Height of the ground (before caves are dug) at this position
+    pub fn surface_height((x, y, z): (f64, f64, f64), world_noise: &WorldNoise) -> f64 {
+        let surface_noise = Manager::sample_layer(world_noise, Manager::SURFACE, (x, y, z));
         // For trees or surface items
 
-        let varitation_noise = world_noise[Manager::VARIATIONS]
-            .get((x * scale, y * scale, z * scale))
-            .powf(2.0);
-        let detail_noise = world_noise[Manager::DETAIL]
-            .get((x * scale, y * scale, z * scale))
-            .powf(2.0);
+        let varitation_noise = Manager::sample_layer(world_noise, Manager::VARIATIONS, (x, y, z)).powf(2.0);
+        let detail_noise = Manager::sample_layer(world_noise, Manager::DETAIL, (x, y, z)).powf(2.0);
 
-        let mut surface_height = surface_noise + varitation_noise + detail_noise;
+        let surface_height = surface_noise + varitation_noise + detail_noise;
 
         // Normalization 0..CHUNK_HEIGHT
-        surface_height = (surface_height + 1.0) * ((HEIGHT as f64) / 2.0);
-
-        let surface = Self::surface(surface_height, (x, y, z), &biome);
-
-        // let ores = Self::ores((x, y, z), world_noise);
-        let cave = Self::cave_noise((x, y, z), world_noise, &biome);
+        (surface_height + 1.0) * ((HEIGHT as f64) / 2.0)
+    }
 
-        if z <= surface_height || (cave == Tile::AIR && surface != Tile::WATER) {
-            cave
-        } else if z > surface_height {
-            surface
-        } else {
-            Tile::AIR
-        }
+    pub fn tile_at((x, y, z): (i32, i32, i32), world_noise: &WorldNoise) -> Tile {
+        Column::sample((x, y), world_noise).tile(z, world_noise)
     }
 }
@@ -0,0 +1,112 @@
+//! Golden snapshots of the world generation
+//!
+//! If generation changes on purpose, run `cargo test golden -- --nocapture`
+//! and copy the new hashes into GOLDEN
+use crate::chunk::{
+    tile::{ Tile, TileType },
+    Chunk,
+    ChunkContent,
+    ChunkManager,
+    HEIGHT,
+    SEA_LEVEL,
+    WIDTH,
+};
+
+const GOLDEN_SEED: u32 = ChunkManager::DEFAULT_SEED;
+
+/// (chunk position, hash of its content)
+const GOLDEN: [((i32, i32), u64); 5] = [
+    ((0, 0), 0x6596a0ce71db879e),
+    ((-1, 0), 0x98a06a3149261391),
+    ((0, -1), 0x4f809a8a6dee8f36),
+    ((3, -2), 0x74f617861308dfc5),
+    ((-7, 5), 0xd0cb2cdd8528d4bd),
+];
+
+/// FNV-1a, stable across platforms and compiler versions (unlike DefaultHasher)
+fn content_hash(content: &ChunkContent) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+
+    for i in 0..ChunkContent::FLAT_SIZE {
+        for byte in bincode::serialize(&content[i]).unwrap() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn generate(seed: u32, positions: &[(i32, i32)]) -> Vec<ChunkContent> {
+    let mngr = ChunkManager::with_seed(seed);
+    let handles: Vec<_> = positions
+        .iter()
+        .map(|pos| Chunk::generate(*pos, &mngr.world_noise, &mngr.rivers))
+        .collect();
+
+    handles
+        .into_iter()
+        .map(|h| h.join().unwrap().access_content())
+        .collect()
+}
+
+#[test]
+fn golden_chunks() {
+    let positions: Vec<_> = GOLDEN.iter().map(|(pos, _)| *pos).collect();
+    let mut changed = vec![];
+
+    for ((pos, golden), content) in GOLDEN.iter().zip(generate(GOLDEN_SEED, &positions)) {
+        let hash = content_hash(&content);
+        if hash != *golden {
+            changed.push(format!("    ({:?}, {:#018x}),", pos, hash));
+        }
+    }
+
+    assert!(changed.is_empty(), "Generated chunks changed :\n{}", changed.join("\n"));
+}
+
+#[test]
+fn same_seed_same_world() {
+    let pos = [(2, -3)];
+
+    let first = generate(GOLDEN_SEED, &pos);
+    let second = generate(GOLDEN_SEED, &pos);
+    let other_seed = generate(GOLDEN_SEED + 1, &pos);
+
+    assert!(first == second);
+    assert!(first != other_seed);
+}
+
+#[test]
+fn generation_invariants() {
+    let mngr = ChunkManager::with_seed(GOLDEN_SEED);
+    let positions: Vec<_> = GOLDEN.iter().map(|(pos, _)| *pos).collect();
+
+    for (pos, content) in positions.iter().zip(generate(GOLDEN_SEED, &positions)) {
+        // Rivers and lakes are the only water allowed above sea level
+        let water_columns: Vec<_> = Chunk::water_columns(*pos, &mngr.world_noise, &mngr.rivers)
+            .into_iter()
+            .map(|(column, _level)| column)
+            .collect();
+
+        for x in 0..WIDTH as i32 {
+            for y in 0..WIDTH as i32 {
+                assert_eq!(content[(x, y, 0)], Tile::BEDROCK, "No bedrock under {:?}", (x, y));
+
+                for z in 0..HEIGHT as i32 {
+                    let tile = content[(x, y, z)];
+
+                    assert!(tile.tile_type != TileType::ERROR, "ERROR tile at {:?}", (x, y, z));
+
+                    if tile.tile_type.is_fluid() && z > (SEA_LEVEL as i32) {
+                        assert!(
+                            water_columns.contains(&(x, y)),
+                            "Water above sea level at {:?} in chunk {:?}",
+                            (x, y, z),
+                            pos
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
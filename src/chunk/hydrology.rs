@@ -0,0 +1,312 @@
+use std::{ collections::HashMap, sync::{ Arc, Mutex, OnceLock } };
+
+use noise::NoiseFn;
+
+use crate::chunk::{
+    generation::column_hash,
+    manager::WorldNoise,
+    tile::Tile,
+    Chunk,
+    ChunkManager as Manager,
+    HEIGHT,
+    SEA_LEVEL,
+    WIDTH,
+};
+
+/// There is at most one river source in each RIVER_CELL * RIVER_CELL square
+const RIVER_CELL: i32 = 64;
+/// Distance (in tiles) between two traced points of a river
+const RIVER_STEP: i32 = 2;
+const MAX_RIVER_STEPS: i32 = 64;
+/// How far a river can flow from its source
+const MAX_RIVER_LENGTH: i32 = RIVER_STEP * MAX_RIVER_STEPS;
+/// Rivers get one tile wider every RIVER_WIDENING steps
+const RIVER_WIDENING: i32 = 24;
+
+/// Scale used to sample the ELEVATION layer for hydrology
+/// (the biome scale is way too slow to give any slope)
+const HYDROLOGY_SCALE: f64 = 0.005;
+/// How much the elevation layer weighs against the terrain height
+const ELEVATION_WEIGHT: f64 = 8.0;
+/// Rivers only spring from high places
+const SOURCE_ELEVATION: f64 = 0.1;
+const SOURCE_MIN_HEIGHT: f64 = 6.0;
+
+/// Dirt laid on top of the surface height (see Chunk::surface)
+const SOIL_DEPTH: f64 = 5.0;
+
+const LAKE_RADIUS: i32 = 6;
+const LAKE_DEPTH: i32 = 2;
+
+const SOURCE_SALT_X: u64 = 27;
+const SOURCE_SALT_Y: u64 = 72;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Basin filled with water where a river could not flow any lower
+#[derive(Debug, Clone, Copy)]
+pub struct Lake {
+    pub center: (i32, i32),
+    pub radius: i32,
+    /// Water surface z
+    pub level: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct River {
+    /// Every column the river flows through
+    pub bed: Vec<(i32, i32)>,
+    pub lake: Option<Lake>,
+}
+
+/// Rivers traced so far, by the cell they spring from
+/// A river crosses many chunks, it's only traced by the first one generated
+#[derive(Debug, Clone, Default)]
+pub struct Rivers(Arc<Mutex<HashMap<(i32, i32), Traced>>>);
+
+/// Set once the river of a cell is traced, None if there is no river there
+type Traced = Arc<OnceLock<Option<Arc<River>>>>;
+
+impl Rivers {
+    pub fn in_cell(&self, cell: (i32, i32), world_noise: &WorldNoise) -> Option<Arc<River>> {
+        // Not locked while tracing, chunks needing the same river wait for it instead
+        let traced = Arc::clone(self.0.lock().unwrap().entry(cell).or_default());
+        traced
+            .get_or_init(|| {
+                River::source_in_cell(cell, world_noise).map(|source| Arc::new(River::trace(source, world_noise)))
+            })
+            .clone()
+    }
+}
+
+/// Approximate z of the ground top, ignoring caves
+fn ground_level((x, y): (i32, i32), world_noise: &WorldNoise) -> f64 {
+    Chunk::surface_height((x as f64, y as f64, SEA_LEVEL as f64), world_noise) + SOIL_DEPTH
+}
+
+fn elevation((x, y): (i32, i32), world_noise: &WorldNoise) -> f64 {
+    world_noise[Manager::ELEVATION].fbm.get([
+        (x as f64) * HYDROLOGY_SCALE,
+        (y as f64) * HYDROLOGY_SCALE,
+        15.0,
+    ])
+}
+
+/// What water follows when going downhill
+fn height(p: (i32, i32), world_noise: &WorldNoise) -> f64 {
+    ground_level(p, world_noise) + elevation(p, world_noise) * ELEVATION_WEIGHT
+}
+
+impl River {
+    /// Where the river of this cell springs, if there is one
+    pub fn source_in_cell(cell: (i32, i32), world_noise: &WorldNoise) -> Option<(i32, i32)> {
+        let source = (
+            cell.0 * RIVER_CELL + ((column_hash(cell, SOURCE_SALT_X) * (RIVER_CELL as f64)) as i32),
+            cell.1 * RIVER_CELL + ((column_hash(cell, SOURCE_SALT_Y) * (RIVER_CELL as f64)) as i32),
+        );
+
+        if
+            elevation(source, world_noise) > SOURCE_ELEVATION &&
+            ground_level(source, world_noise) > (SEA_LEVEL as f64) + SOURCE_MIN_HEIGHT
+        {
+            Some(source)
+        } else {
+            None
+        }
+    }
+
+    /// Follows the steepest slope from the source until the river reaches the sea,
+    /// gets stuck in a basin (and makes a lake) or is too long
+    pub fn trace(source: (i32, i32), world_noise: &WorldNoise) -> Self {
+        let mut bed = vec![];
+        let mut current = source;
+        let mut current_height = height(current, world_noise);
+
+        for step in 0..MAX_RIVER_STEPS {
+            // Reached the sea
+            if ground_level(current, world_noise) <= (SEA_LEVEL as f64) {
+                return Self { bed, lake: None };
+            }
+
+            let ((dx, dy), next_height) = NEIGHBOURS.iter()
+                .map(|&(dx, dy)| {
+                    let next = (current.0 + dx * RIVER_STEP, current.1 + dy * RIVER_STEP);
+                    ((dx, dy), height(next, world_noise))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+
+            // Nowhere lower to go
+            if next_height >= current_height {
+                return Self { bed, lake: Lake::fill(current, world_noise) };
+            }
+
+            let width = 1 + step / RIVER_WIDENING;
+            for i in 0..RIVER_STEP {
+                let (x, y) = (current.0 + dx * i, current.1 + dy * i);
+                bed.push((x, y));
+                for w in 1..width {
+                    bed.push((x + w, y));
+                    bed.push((x, y + w));
+                }
+            }
+
+            current = (current.0 + dx * RIVER_STEP, current.1 + dy * RIVER_STEP);
+            current_height = next_height;
+        }
+
+        Self { bed, lake: None }
+    }
+}
+
+impl Lake {
+    pub fn fill(center: (i32, i32), world_noise: &WorldNoise) -> Option<Self> {
+        // Top of the column, caves included this time
+        let bottom = (0..=ground_level(center, world_noise).ceil() as i32)
+            .rev()
+            .find(|z| Chunk::tile_at((center.0, center.1, *z), world_noise) != Tile::AIR)?;
+        // Lakes up in the mountains still fit in the chunk
+        let level = (bottom + LAKE_DEPTH).min((HEIGHT as i32) - 1);
+
+        // Sea water is already there
+        if level <= (SEA_LEVEL as i32) {
+            return None;
+        }
+
+        Some(Self { center, radius: LAKE_RADIUS, level })
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        let (dx, dy) = (x - self.center.0, y - self.center.1);
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
+
+impl Chunk {
+    /// Carves every river and lake flowing through this chunk
+    pub fn carve_water(&mut self, chunk_pos: (i32, i32), world_noise: &WorldNoise, rivers: &Rivers) {
+        for (column, level) in Self::water_columns(chunk_pos, world_noise, rivers) {
+            self.flood_column(column, level);
+        }
+    }
+
+    /// Columns (in chunk coordinates) crossed by a river, with the lake level if it is a lake
+    /// Rivers are traced from their sources so they match across chunk borders
+    pub fn water_columns(
+        chunk_pos: (i32, i32),
+        world_noise: &WorldNoise,
+        rivers: &Rivers
+    ) -> Vec<((i32, i32), Option<i32>)> {
+        let w = WIDTH as i32;
+        let (x_min, y_min) = (chunk_pos.0 * w, chunk_pos.1 * w);
+        let reach = MAX_RIVER_LENGTH + LAKE_RADIUS;
+
+        let cells = |min: i32| {
+            (min - reach).div_euclid(RIVER_CELL)..=(min + w + reach).div_euclid(RIVER_CELL)
+        };
+        let (cells_x, cells_y) = (cells(x_min), cells(y_min));
+
+        let is_inside = |(x, y): (i32, i32)| {
+            x >= x_min && x < x_min + w && y >= y_min && y < y_min + w
+        };
+        let mut columns = vec![];
+
+        for cx in cells_x {
+            for cy in cells_y.clone() {
+                let Some(river) = rivers.in_cell((cx, cy), world_noise) else {
+                    continue;
+                };
+
+                for &(x, y) in river.bed.iter().filter(|p| is_inside(**p)) {
+                    columns.push(((x - x_min, y - y_min), None));
+                }
+
+                if let Some(lake) = river.lake {
+                    for x in x_min..x_min + w {
+                        for y in y_min..y_min + w {
+                            if lake.contains((x, y)) {
+                                columns.push(((x - x_min, y - y_min), Some(lake.level)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        columns
+    }
+
+    /// Replaces the top of a column with water, up to `level` if there is one
+    fn flood_column(&mut self, (x, y): (i32, i32), level: Option<i32>) {
+        let Some(top) = self.surface_z((x, y)) else {
+            return;
+        };
+        // Already underwater
+        if top < (SEA_LEVEL as i32) {
+            return;
+        }
+
+        let level = level.unwrap_or(top);
+        for z in top..=level {
+            self.set((x, y, z), Tile::WATER);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::chunk::{ hydrology::River, tile::Tile, Chunk, ChunkManager, WIDTH };
+
+    #[test]
+    fn rivers_are_traced_once() {
+        let mngr = ChunkManager::empty();
+        let cell = (-4..4)
+            .flat_map(|x| (-4..4).map(move |y| (x, y)))
+            .find(|cell| River::source_in_cell(*cell, &mngr.world_noise).is_some())
+            .expect("No river around spawn");
+
+        let first = mngr.rivers.in_cell(cell, &mngr.world_noise).unwrap();
+        let again = mngr.rivers.in_cell(cell, &mngr.world_noise).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+    }
+
+    #[test]
+    fn rivers_are_carved_across_chunks() {
+        let mngr = ChunkManager::empty();
+        let source = (-4..4)
+            .flat_map(|x| (-4..4).map(move |y| (x, y)))
+            .find_map(|cell| River::source_in_cell(cell, &mngr.world_noise))
+            .expect("No river around spawn");
+        let river = River::trace(source, &mngr.world_noise);
+        assert!(!river.bed.is_empty(), "River at {:?} has no bed", source);
+
+        // Pick bed tiles in two different chunks
+        let w = WIDTH as i32;
+        let chunk_of = |(x, y): (i32, i32)| (x.div_euclid(w), y.div_euclid(w));
+        let first = river.bed[0];
+        let other = river.bed
+            .iter()
+            .find(|p| chunk_of(**p) != chunk_of(first))
+            .copied()
+            .expect("River should cross a chunk border");
+
+        for (x, y) in [first, other] {
+            let chunk_pos = chunk_of((x, y));
+            let chunk = Chunk::generate(chunk_pos, &mngr.world_noise, &mngr.rivers).join().unwrap();
+            let column = chunk.access_content().column((x.rem_euclid(w), y.rem_euclid(w)));
+
+            assert!(column.contains(&Tile::WATER), "River bed at {:?} is dry", (x, y));
+        }
+    }
+}
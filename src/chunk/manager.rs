@@ -1,18 +1,33 @@
 use std::{ collections::HashMap, sync::{ mpsc::{ Receiver, Sender }, Arc, Mutex } };
 
-use crate::{ ant::{ colony::Colony, AntManager }, chunk::biomes::NoiseParams, renderer::Renderer };
+use crate::{ ant::{ colony::Colony, AntManager }, chunk::biomes::{ Biome, NoiseParams }, renderer::Renderer };
 
 use super::{
     generation::{ MapShape, STARTING_AREA, STARTING_MAP_SHAPE },
-    tile::Tile,
+    hydrology::Rivers,
+    index::flatten_index_i32,
+    tile::{ Tile, TileFlag },
     Chunk,
+    HEIGHT,
     WIDTH,
 };
 
 pub type WorldNoise = Arc<[NoiseParams; 10]>;
 
+/// Surface temperature of a mild climate, in °C
+const MILD_TEMPERATURE: f32 = 20.0;
+/// How far the climate moves it either way
+const CLIMATE_SWING: f32 = 15.0;
+/// Deep underground it's always the same
+const DEEP_TEMPERATURE: f32 = 16.0;
+/// Solid tiles above after which only the deep temperature is felt
+const INSULATION_DEPTH: i32 = 10;
+
 pub struct Manager {
+    pub seed: u32,
     pub world_noise: WorldNoise,
+    /// Shared by the generation threads
+    pub rivers: Rivers,
     pub rx: Sender<LoadedChunk>,
     pub tx: Receiver<LoadedChunk>,
     pub pending_chunks: Vec<LoadedChunk>,
@@ -35,16 +50,100 @@ impl Manager {
         }
     }
 
+    /// World coordinates -> (chunk position, coordinates inside this chunk)
+    pub fn to_local(p: (i32, i32, i32)) -> ((i32, i32), (i32, i32, i32)) {
+        let w = WIDTH as i32;
+        ((p.0.div_euclid(w), p.1.div_euclid(w)), (p.0.rem_euclid(w), p.1.rem_euclid(w), p.2))
+    }
+
     pub fn tile_at(&self, p: (i32, i32, i32)) -> Option<Tile> {
-        let chunk_pos = (p.0.div_euclid(WIDTH as i32), p.1.div_euclid(WIDTH as i32));
-        for (_pos, loaded_chunk) in &self.loaded_chunks {
-            if loaded_chunk.pos == chunk_pos {
-                return Some(loaded_chunk.c.lock().unwrap().get(p));
+        // Nothing above the sky or below the bedrock
+        if !(0..HEIGHT as i32).contains(&p.2) {
+            return None;
+        }
+        let (chunk_pos, local) = Self::to_local(p);
+        // None if this tile is not in loaded chunks
+        self.loaded_chunks
+            .get(&chunk_pos)
+            .map(|loaded_chunk| loaded_chunk.c.lock().unwrap().get(local))
+    }
+
+    /// Every tile between `from` and `to` (both included), x first then y then z
+    /// Each chunk is locked once, unlike tile_at. None where nothing is loaded
+    pub fn tiles_in(&self, from: (i32, i32, i32), to: (i32, i32, i32)) -> Vec<Option<Tile>> {
+        let (w, h) = (to.0 - from.0 + 1, to.1 - from.1 + 1);
+        let mut tiles = vec![None; (w * h * (to.2 - from.2 + 1)).max(0) as usize];
+        let (first, _) = Self::to_local(from);
+        let (last, _) = Self::to_local(to);
+        let width = WIDTH as i32;
+
+        for cx in first.0..=last.0 {
+            for cy in first.1..=last.1 {
+                let Some(loaded_chunk) = self.loaded_chunks.get(&(cx, cy)) else {
+                    continue;
+                };
+                let chunk = loaded_chunk.c.lock().unwrap();
+                let (x_min, x_max) = (from.0.max(cx * width), to.0.min(cx * width + width - 1));
+                let row = (x_max - x_min + 1) as usize;
+                for z in from.2.max(0)..=to.2.min((HEIGHT as i32) - 1) {
+                    for y in from.1.max(cy * width)..=to.1.min(cy * width + width - 1) {
+                        // A row at once
+                        let i = ((x_min - from.0) + (y - from.1) * w + (z - from.2) * w * h) as usize;
+                        let j = flatten_index_i32((x_min - cx * width, y - cy * width, z));
+                        for (tile, src) in tiles[i..i + row].iter_mut().zip(&chunk.content.0[j..j + row]) {
+                            *tile = Some(*src);
+                        }
+                    }
+                }
             }
         }
-        panic!("Failed to find tile at {:?}", p);
-        // Could not find this tile in loaded chunks
-        None
+        tiles
+    }
+
+    pub fn set_tile(&self, p: (i32, i32, i32), tile: Tile) {
+        if !(0..HEIGHT as i32).contains(&p.2) {
+            return;
+        }
+        let (chunk_pos, local) = Self::to_local(p);
+        if let Some(loaded_chunk) = self.loaded_chunks.get(&chunk_pos) {
+            loaded_chunk.c.lock().unwrap().set(local, tile);
+        }
+    }
+
+    /// Hits the tile at `p`, harder tiles take more hits
+    /// Returns what was dug out once the tile breaks
+    pub fn dig(&self, p: (i32, i32, i32), strength: u8) -> Option<Tile> {
+        let tile = self.tile_at(p)?;
+        let hardness = tile.hardness();
+        if hardness == 0 {
+            return None;
+        }
+
+        let damage = (strength / hardness).max(1);
+        if tile.hp <= damage {
+            self.set_tile(p, Tile::AIR);
+            // Dug out tiles are as good as new
+            Some(Tile { hp: 100, ..tile })
+        } else {
+            self.set_tile(p, Tile { hp: tile.hp - damage, ..tile });
+            None
+        }
+    }
+
+    /// Rough temperature at `p` in °C : the climate outside, steadier under the ground
+    pub fn temperature_at(&self, p: (i32, i32, i32)) -> f32 {
+        let climate = Biome::get_biome_params(p.0 as f64, p.1 as f64, &self.world_noise);
+        let surface = MILD_TEMPERATURE + CLIMATE_SWING * (climate.temperature as f32);
+
+        let covered = (1..=INSULATION_DEPTH)
+            .filter(|dz| {
+                self.tile_at((p.0, p.1, p.2 + dz))
+                    .is_some_and(|tile| !tile.properties.contains(TileFlag::TRAVERSABLE))
+            })
+            .count() as f32;
+        let insulation = covered / (INSULATION_DEPTH as f32);
+
+        surface + (DEEP_TEMPERATURE - surface) * insulation
     }
 }
 
@@ -73,3 +172,21 @@ impl Default for Manager {
         return mngr;
     }
 }
+
+#[cfg(test)]
+impl Manager {
+    /// Two chunks side by side, with a dirt floor at z = 1
+    pub fn flat_world() -> Self {
+        let mut mngr = Self::empty();
+        for pos in [(0, 0), (1, 0)] {
+            mngr.loaded_chunks.insert(pos, LoadedChunk::new(pos));
+        }
+        for x in 0..(WIDTH as i32) * 2 {
+            for y in 0..WIDTH as i32 {
+                mngr.set_tile((x, y, 0), Tile::BEDROCK);
+                mngr.set_tile((x, y, 1), Tile::DIRT);
+            }
+        }
+        mngr
+    }
+}
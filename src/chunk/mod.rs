@@ -4,6 +4,7 @@ use tile::Tile;
 
 pub mod biomes;
 pub mod generation;
+pub mod hydrology;
 pub mod manager;
 
 /// Name export so it's not confused with Ant::Manager
@@ -15,6 +16,12 @@ use crate::chunk::{ index::to_xyz, tile::TileFlag };
 pub mod tile;
 pub mod index;
 pub mod thread;
+pub mod vegetation;
+
+#[cfg(test)]
+mod golden;
+#[cfg(test)]
+mod bench;
 
 /// Chunk's data
 #[derive(Hash, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
@@ -116,7 +123,7 @@ mod tests {
         let pos = (random(), random());
         println!("Generating chunk at {:?}", pos);
 
-        let chunk = Chunk::generate(pos, &manager.world_noise);
+        let chunk = Chunk::generate(pos, &manager.world_noise, &manager.rivers);
 
         let chunk = chunk.join().expect("Failed to collect created chunk");
         println!("{:?}: \n\n", chunk.c);
@@ -20,6 +20,7 @@ bitflags! {
         const INTERACTIBLE = 0b0010000;
         const HAS_STATE    = 0b0100000;
         const TRANSPARENT  = 0b1000000;
+        const EDIBLE       = 0b10000000;
     }
 }
 
@@ -31,6 +32,7 @@ impl fmt::Debug for Tile {
             TileType::Soil(_soil) => "x",
             TileType::Gas(_gas) => "'",
             TileType::Fluid(_fluid) => "~",
+            TileType::Food(_food) => "*",
             TileType::Custom(_) => "?",
         })?;
         Ok(())
@@ -52,6 +54,7 @@ pub enum TileType {
     Soil(Soil),
     Gas(Gas),
     Fluid(Fluid),
+    Food(Food),
     Custom(u16),
 }
 impl TileType {
@@ -118,6 +121,41 @@ impl Tile {
         tile_type: TileType::WATER,
         properties: TileFlag::FLUID,
     };
+
+    // Food tiles use `hp` as the quantity left to harvest
+    pub const GRASS: Tile = Tile::food(Food::Grass);
+    pub const SEEDS: Tile = Tile::food(Food::Seeds);
+    pub const FRUIT: Tile = Tile::food(Food::Fruit);
+    pub const DEAD_INSECT: Tile = Tile::food(Food::DeadInsect);
+
+    const fn food(food: Food) -> Tile {
+        Tile {
+            hp: food.max_quantity(),
+            tile_type: TileType::Food(food),
+            properties: TileFlag::GAS.union(TileFlag::EDIBLE).union(TileFlag::HAS_STATE),
+        }
+    }
+
+    /// How much food can still be harvested on this tile
+    pub fn food_quantity(self) -> u8 {
+        if self.properties.contains(TileFlag::EDIBLE) { self.hp } else { 0 }
+    }
+
+    /// How long it takes to dig through, 0 if it can't be dug
+    pub fn hardness(self) -> u8 {
+        if !self.properties.contains(TileFlag::DIGGABLE) {
+            return 0;
+        }
+        match self.tile_type {
+            TileType::Soil(Soil::Sand) => 1,
+            TileType::Soil(Soil::Dirt) => 2,
+            TileType::Soil(Soil::Clay) => 3,
+            TileType::Stone(Stone::Limestone) => 5,
+            TileType::Stone(Stone::Marble) => 6,
+            TileType::Stone(Stone::Granite) => 8,
+            _ => 0,
+        }
+    }
 }
 
 #[derive(Hash, Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
@@ -133,6 +171,29 @@ pub enum Gas {
     Air,
 }
 
+#[derive(Hash, Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u16)]
+pub enum Food {
+    Grass,
+    Seeds,
+    Fruit,
+    DeadInsect,
+}
+impl Food {
+    pub const fn max_quantity(self) -> u8 {
+        match self {
+            Food::Grass => 10,
+            Food::Seeds => 5,
+            Food::Fruit => 20,
+            Food::DeadInsect => 30,
+        }
+    }
+    /// Corpses are eaten once and for all, plants grow back
+    pub fn regrows(self) -> bool {
+        !matches!(self, Food::DeadInsect)
+    }
+}
+
 #[derive(Hash, Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u16)]
 pub enum Soil {
@@ -184,6 +245,14 @@ impl Into<String> for TileType {
                     // _ => "Gas",
                 }
             }
+            TileType::Food(f) => {
+                match f {
+                    Food::Grass => "Grass",
+                    Food::Seeds => "Seeds",
+                    Food::Fruit => "Fruit",
+                    Food::DeadInsect => "Dead insect",
+                }
+            }
             t => todo!("Unknown tiletype : {:?}", t),
             // TileType::Custom(_) => todo!("Custom tile type"),
         })
@@ -215,6 +284,13 @@ impl Tile {
                     Fluid::SaltWater => Color::RGBA(0, 0, 200, 150),
                     Fluid::Magma => Color::RGBA(255, 0, 0, 200),
                 }
+            TileType::Food(food) =>
+                match food {
+                    Food::Grass => Color::RGBA(60, 160, 40, 200),
+                    Food::Seeds => Color::RGBA(210, 180, 90, 220),
+                    Food::Fruit => Color::RGBA(200, 30, 90, 230),
+                    Food::DeadInsect => Color::RGBA(40, 30, 20, 230),
+                }
             TileType::Custom(_error) => Color::RGB(255, 155, 200),
         }
     }
@@ -0,0 +1,162 @@
+use crate::{
+    chunk::{
+        biomes::BiomeKind,
+        generation::{ column_hash, Column },
+        tile::{ Tile, TileFlag, TileType },
+        Chunk,
+        ChunkContent,
+        ChunkManager as Manager,
+        HEIGHT,
+        SEA_LEVEL,
+        WIDTH,
+    },
+    time::TICKS_PER_MINUTE,
+};
+
+/// Plants get one more unit of food every REGROWTH_TICKS
+pub const REGROWTH_TICKS: u64 = TICKS_PER_MINUTE * 5;
+
+const VEGETATION_SALT: u64 = 26;
+
+impl BiomeKind {
+    /// What grows in this biome, and how likely it is on a single surface tile
+    fn vegetation(self) -> &'static [(Tile, f64)] {
+        match self {
+            BiomeKind::Grassland =>
+                &[(Tile::GRASS, 0.35), (Tile::SEEDS, 0.08), (Tile::DEAD_INSECT, 0.01)],
+            BiomeKind::Forest =>
+                &[(Tile::GRASS, 0.2), (Tile::FRUIT, 0.1), (Tile::SEEDS, 0.05), (Tile::DEAD_INSECT, 0.02)],
+            BiomeKind::Swamp => &[(Tile::GRASS, 0.3), (Tile::DEAD_INSECT, 0.03)],
+            BiomeKind::Desert => &[(Tile::SEEDS, 0.03), (Tile::DEAD_INSECT, 0.01)],
+            BiomeKind::Tundra => &[(Tile::GRASS, 0.05), (Tile::DEAD_INSECT, 0.005)],
+        }
+    }
+}
+
+impl Chunk {
+    /// Topmost solid tile of a column
+    pub fn surface_z(&self, (x, y): (i32, i32)) -> Option<i32> {
+        (0..HEIGHT as i32)
+            .rev()
+            .find(|z| !self.get((x, y, *z)).properties.contains(TileFlag::TRAVERSABLE))
+    }
+
+    /// Covers the surface with food, depending on the local biome
+    pub fn grow_vegetation(&mut self, columns: &[Column]) {
+        for column in columns {
+            let (wx, wy) = column.pos;
+            let (x, y) = (wx.rem_euclid(WIDTH as i32), wy.rem_euclid(WIDTH as i32));
+
+            let Some(z) = self.surface_z((x, y)) else {
+                continue;
+            };
+            // Nothing grows underwater
+            if z < (SEA_LEVEL as i32) || z + 1 >= (HEIGHT as i32) {
+                continue;
+            }
+            if self.get((x, y, z + 1)) != Tile::AIR {
+                continue;
+            }
+
+            if let Some(food) = Self::vegetation_at((wx, wy), column.biome.kind()) {
+                self.set((x, y, z + 1), food);
+            }
+        }
+    }
+
+    fn vegetation_at((x, y): (i32, i32), kind: BiomeKind) -> Option<Tile> {
+        let mut roll = column_hash((x, y), VEGETATION_SALT);
+
+        for (tile, chance) in kind.vegetation() {
+            if roll < *chance {
+                return Some(*tile);
+            }
+            roll -= chance;
+        }
+        None
+    }
+}
+
+impl Manager {
+    pub fn tick(&self, tick: u64) {
+        if tick.is_multiple_of(REGROWTH_TICKS) {
+            self.grow_back_vegetation();
+        }
+    }
+
+    /// Every plant in loaded chunks gets one more unit of food
+    pub fn grow_back_vegetation(&self) {
+        for loaded in self.loaded_chunks.values() {
+            let mut chunk = loaded.c.lock().unwrap();
+
+            for i in 0..ChunkContent::FLAT_SIZE {
+                let tile = &mut chunk.content[i];
+
+                if let TileType::Food(food) = tile.tile_type {
+                    if food.regrows() && tile.hp < food.max_quantity() {
+                        tile.hp += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Takes up to `amount` food from the tile at `p`
+    /// Returns how much was actually taken
+    pub fn harvest(&self, p: (i32, i32, i32), amount: u8) -> u8 {
+        let Some(tile) = self.tile_at(p) else {
+            return 0;
+        };
+        let TileType::Food(food) = tile.tile_type else {
+            return 0;
+        };
+
+        let taken = tile.hp.min(amount);
+        let left = Tile { hp: tile.hp - taken, ..tile };
+
+        if left.hp == 0 && !food.regrows() {
+            self.set_tile(p, Tile::AIR);
+        } else {
+            self.set_tile(p, left);
+        }
+
+        taken
+    }
+
+    /// Topmost solid tile of a column, None if it's not loaded
+    pub fn surface_z(&self, (x, y): (i32, i32)) -> Option<i32> {
+        let (chunk_pos, (lx, ly, _)) = Self::to_local((x, y, 0));
+        let loaded = self.loaded_chunks.get(&chunk_pos)?;
+        let chunk = loaded.c.lock().unwrap();
+        chunk.surface_z((lx, ly))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::{ manager::LoadedChunk, tile::{ Food, Tile }, ChunkManager };
+
+    #[test]
+    fn harvest_and_regrow() {
+        let mut mngr = ChunkManager::empty();
+        mngr.loaded_chunks.insert((-1, 0), LoadedChunk::new((-1, 0)));
+
+        let grass = (-3, 2, 40);
+        let insect = (-4, 2, 40);
+        mngr.set_tile(grass, Tile::GRASS);
+        mngr.set_tile(insect, Tile::DEAD_INSECT);
+
+        assert_eq!(mngr.harvest(grass, 4), 4);
+        assert_eq!(mngr.tile_at(grass).unwrap().food_quantity(), Food::Grass.max_quantity() - 4);
+
+        mngr.grow_back_vegetation();
+        assert_eq!(mngr.tile_at(grass).unwrap().food_quantity(), Food::Grass.max_quantity() - 3);
+
+        // Corpses don't grow back
+        assert_eq!(mngr.harvest(insect, u8::MAX), Food::DeadInsect.max_quantity());
+        assert_eq!(mngr.tile_at(insect), Some(Tile::AIR));
+
+        // Nothing to eat in the air
+        assert_eq!(mngr.harvest((-5, 2, 40), 1), 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Dumps a region of the world to image files, without opening any window
+//!
+//! `ants_layer export --from X,Y --size W,H [--z Z] [--seed SEED] [--out DIR]`
+//!
+//! Writes a heightmap, a biome map, a material map at `z` and every noise layer,
+//! sampled like the generation does, as PPM/PGM files (any image viewer or `convert` can read them)
+use std::{ fs, io::Write, path::{ Path, PathBuf } };
+
+use sdl2::pixels::Color;
+
+use crate::chunk::{
+    biomes::{ Biome, BiomeKind },
+    manager::LoadedChunk,
+    ChunkManager,
+    HEIGHT,
+    SEA_LEVEL,
+    WIDTH,
+};
+
+pub struct Region {
+    pub from: (i32, i32),
+    pub size: (u32, u32),
+    pub z: i32,
+    pub seed: u32,
+    pub out: PathBuf,
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        Self {
+            from: (-64, -64),
+            size: (128, 128),
+            z: SEA_LEVEL as i32,
+            seed: ChunkManager::DEFAULT_SEED,
+            out: PathBuf::from("export"),
+        }
+    }
+}
+
+impl BiomeKind {
+    pub fn color(self) -> Color {
+        match self {
+            BiomeKind::Desert => Color::RGB(230, 200, 120),
+            BiomeKind::Grassland => Color::RGB(120, 190, 70),
+            BiomeKind::Forest => Color::RGB(30, 110, 40),
+            BiomeKind::Swamp => Color::RGB(70, 90, 60),
+            BiomeKind::Tundra => Color::RGB(220, 230, 240),
+        }
+    }
+}
+
+fn parse_pair<T: std::str::FromStr>(arg: Option<&String>) -> Result<(T, T), String> {
+    let arg = arg.ok_or("Missing value")?;
+    let (a, b) = arg.split_once(',').ok_or(format!("Expected A,B, got {:?}", arg))?;
+
+    match (a.trim().parse(), b.trim().parse()) {
+        (Ok(a), Ok(b)) => Ok((a, b)),
+        _ => Err(format!("Invalid pair {:?}", arg)),
+    }
+}
+
+fn parse_one<T: std::str::FromStr>(arg: Option<&String>) -> Result<T, String> {
+    let arg = arg.ok_or("Missing value")?;
+    arg.parse().map_err(|_| format!("Invalid value {:?}", arg))
+}
+
+impl Region {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut region = Self::default();
+        let mut args = args.iter();
+
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--from" => {
+                    region.from = parse_pair(args.next())?;
+                }
+                "--size" => {
+                    region.size = parse_pair(args.next())?;
+                }
+                "--z" => {
+                    region.z = parse_one(args.next())?;
+                }
+                "--seed" => {
+                    region.seed = parse_one(args.next())?;
+                }
+                "--out" => {
+                    region.out = PathBuf::from(parse_one::<String>(args.next())?);
+                }
+                other => {
+                    return Err(format!("Unknown export option {:?}", other));
+                }
+            }
+        }
+
+        if region.z < 0 || region.z >= (HEIGHT as i32) {
+            return Err(format!("z must be in 0..{}", HEIGHT));
+        }
+        Ok(region)
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let (x0, y0) = self.from;
+        let (w, h) = (self.size.0 as i32, self.size.1 as i32);
+
+        (y0..y0 + h).flat_map(move |y| (x0..x0 + w).map(move |x| (x, y)))
+    }
+
+    pub fn export(&self) -> Result<(), String> {
+        fs::create_dir_all(&self.out).map_err(|e| e.to_string())?;
+
+        let mut mngr = ChunkManager::with_seed(self.seed);
+        let noise = mngr.world_noise.clone();
+
+        // Real chunks, so rivers, lakes and vegetation show up
+        let w = WIDTH as i32;
+        let (x0, y0) = self.from;
+        let (x1, y1) = (x0 + (self.size.0 as i32), y0 + (self.size.1 as i32));
+        let handles = mngr.generate_range(
+            x0.div_euclid(w)..(x1 - 1).div_euclid(w) + 1,
+            y0.div_euclid(w)..(y1 - 1).div_euclid(w) + 1
+        );
+        for h in handles {
+            let chunk: LoadedChunk = h.join().unwrap();
+            mngr.loaded_chunks.insert(chunk.pos, chunk);
+        }
+
+        let heightmap: Vec<u8> = self
+            .pixels()
+            .map(|(x, y)| {
+                let (chunk_pos, (lx, ly, _)) = ChunkManager::to_local((x, y, 0));
+                let top = mngr.loaded_chunks[&chunk_pos].c.lock().unwrap().surface_z((lx, ly));
+                ((top.unwrap_or(0) * 255) / (HEIGHT as i32)) as u8
+            })
+            .collect();
+        self.write_pgm("heightmap.pgm", &heightmap)?;
+
+        let biomes: Vec<Color> = self
+            .pixels()
+            .map(|(x, y)| Biome::get_biome_params(x as f64, y as f64, &noise).kind().color())
+            .collect();
+        self.write_ppm("biomes.ppm", &biomes)?;
+
+        let materials: Vec<Color> = self
+            .pixels()
+            .map(|(x, y)| mngr.tile_at((x, y, self.z)).unwrap().color())
+            .collect();
+        self.write_ppm(&format!("materials_z{}.ppm", self.z), &materials)?;
+
+        for (layer, name) in ChunkManager::LAYER_NAMES.iter().enumerate() {
+            let values: Vec<u8> = self
+                .pixels()
+                .map(|(x, y)| {
+                    let v = ChunkManager::sample_layer(&noise, layer, (x as f64, y as f64, self.z as f64));
+                    ((v.clamp(-1.0, 1.0) + 1.0) * 127.5) as u8
+                })
+                .collect();
+            self.write_pgm(&format!("noise_{}.pgm", name), &values)?;
+        }
+
+        println!("Exported {:?} from {:?} to {:?}", self.size, self.from, self.out);
+        Ok(())
+    }
+
+    fn write_pgm(&self, name: &str, values: &[u8]) -> Result<(), String> {
+        write_image(&self.out.join(name), "P5", self.size, values)
+    }
+
+    fn write_ppm(&self, name: &str, colors: &[Color]) -> Result<(), String> {
+        let bytes: Vec<u8> = colors
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b])
+            .collect();
+        write_image(&self.out.join(name), "P6", self.size, &bytes)
+    }
+}
+
+fn write_image(path: &Path, magic: &str, (w, h): (u32, u32), bytes: &[u8]) -> Result<(), String> {
+    let mut file = fs::File::create(path).map_err(|e| format!("{:?} : {}", path, e))?;
+
+    write!(file, "{}\n{} {}\n255\n", magic, w, h).map_err(|e| e.to_string())?;
+    file.write_all(bytes).map_err(|e| e.to_string())
+}
+
+/// `ants_layer export ...`
+pub fn run(args: &[String]) -> Result<(), String> {
+    Region::from_args(args)?.export()
+}
+
+#[test]
+fn export_small_region() {
+    let out = std::env::temp_dir().join("ants_layer_export_test");
+    let args: Vec<String> = ["--from", "-5,-3", "--size", "10,6", "--out", out.to_str().unwrap()]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    run(&args).unwrap();
+
+    let heightmap = fs::read(out.join("heightmap.pgm")).unwrap();
+    assert!(heightmap.starts_with(b"P5\n10 6\n255\n"));
+    assert_eq!(heightmap.len(), "P5\n10 6\n255\n".len() + 10 * 6);
+
+    let biomes = fs::read(out.join("biomes.ppm")).unwrap();
+    assert_eq!(biomes.len(), "P6\n10 6\n255\n".len() + 10 * 6 * 3);
+
+    assert!(out.join(format!("materials_z{}.ppm", SEA_LEVEL)).exists());
+    assert!(out.join("noise_caves.pgm").exists());
+
+    // What the biomes are made of
+    let humidity = fs::read(out.join("noise_humidity.pgm")).unwrap();
+    let biome = Biome::get_biome_params(-5.0, -3.0, &ChunkManager::empty().world_noise);
+    assert_eq!(humidity["P5\n10 6\n255\n".len()], ((biome.humidity + 1.0) * 127.5) as u8);
+}
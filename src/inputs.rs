@@ -1,6 +1,14 @@
 use sdl2::{ event::Event, keyboard::Keycode, mouse::MouseButton };
 
-use crate::{ ant::direction::Direction, chunk::biomes::Biome, interface::{ self }, Game };
+use crate::{
+    ant::{ designation::{ Area, Designation }, direction::Direction },
+    chunk::biomes::Biome,
+    interface::{ self },
+    Game,
+};
+
+/// Ticks run each frame at most
+const MAX_SPEED: u64 = 16;
 
 pub trait ToDirection {
     fn to_direction(self) -> Result<Direction, Keycode>;
@@ -15,6 +23,15 @@ pub trait ToDirection {
 /// S => South
 /// Q => West
 /// D => East
+/// 1 => Dig designation
+/// 2 => Build designation
+/// 3 => Keep out designation
+/// X => No designation
+/// R => Send the soldiers to the tile under the mouse
+/// F => Call the soldiers back
+/// B => Show what each ant is busy with
+/// Keypad plus / minus => Speed the game up / down
+/// Right click => Cancel the designation under the cursor
 impl ToDirection for Keycode {
     fn to_direction(self) -> Result<Direction, Keycode> {
         match self {
@@ -39,6 +56,12 @@ pub struct Inputs {
     pub just_pressed_keys: Vec<Keycode>,
     // save slider id when dragged
     pub dragging_slider_id: Option<interface::Id>,
+    // Designation drawn when dragging the mouse, if any
+    pub tool: Option<Designation>,
+    // Tile where the drag started
+    pub selection_start: Option<(i32, i32)>,
+    // Tile under the mouse
+    pub mouse_tile: (i32, i32),
 }
 
 impl Inputs {
@@ -47,6 +70,9 @@ impl Inputs {
             pressed_keys: Vec::new(),
             just_pressed_keys: Vec::new(),
             dragging_slider_id: None,
+            tool: None,
+            selection_start: None,
+            mouse_tile: (0, 0),
             // mouse_pressed: Vec::new(),
             // mouse_position: (0, 0),
             // wheel_dir: 0,
@@ -61,6 +87,12 @@ impl Inputs {
         self.just_pressed_keys.contains(&key)
     }
 
+    /// Dragged area, at the camera z
+    pub fn selection(&self, z: i32) -> Option<Area> {
+        let (x, y) = self.selection_start?;
+        Some(Area::new((x, y, z), (self.mouse_tile.0, self.mouse_tile.1, z)))
+    }
+
     // pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
     //     self.mouse_pressed.contains(&button)
     // }
@@ -90,8 +122,18 @@ impl<'ttf> Game<'ttf> {
                     // Reset all sliders on mouse release
                     self.interface.clear_sliders_state();
                     self.inputs.dragging_slider_id = None;
+
+                    // Designations are made on release
+                    let area = self.inputs.selection(self.renderer.camera.2);
+                    let player = self.ant_manager.player_mut();
+                    if let (Some(area), Some(tool), Some(player)) = (area, self.inputs.tool, player) {
+                        player.designate(area, tool, &self.chunk_manager);
+                    }
+                    self.inputs.selection_start = None;
                 }
-                Event::MouseMotion { x, .. } => {
+                Event::MouseMotion { x, y, .. } => {
+                    self.inputs.mouse_tile = self.renderer.screen_to_tile_coords((x, y));
+
                     //
                     if let Some(id) = self.inputs.dragging_slider_id {
                         let slider_value = self.interface.update(id, x);
@@ -145,9 +187,41 @@ impl<'ttf> Game<'ttf> {
                         self.renderer.increase_view_dist().unwrap();
                     }
                     Keycode::RETURN => {
-                        self.renderer.center_on_queen(
-                            &self.ant_manager.colonies[crate::ant::colony::Colony::PLAYER]
-                        );
+                        if let Some(player) = self.ant_manager.player() {
+                            self.renderer.center_on_queen(player);
+                        }
+                    }
+                    Keycode::NUM_1 => {
+                        self.inputs.tool = Some(Designation::Dig);
+                    }
+                    Keycode::NUM_2 => {
+                        self.inputs.tool = Some(Designation::Build);
+                    }
+                    Keycode::NUM_3 => {
+                        self.inputs.tool = Some(Designation::KeepOut);
+                    }
+                    Keycode::X => {
+                        self.inputs.tool = None;
+                    }
+                    Keycode::R => {
+                        let (x, y) = self.inputs.mouse_tile;
+                        if let Some(player) = self.ant_manager.player_mut() {
+                            player.raid((x, y, self.renderer.camera.2), &self.chunk_manager);
+                        }
+                    }
+                    Keycode::F => {
+                        if let Some(player) = self.ant_manager.player_mut() {
+                            player.call_back();
+                        }
+                    }
+                    Keycode::B => {
+                        self.renderer.is_behavior_shown = !self.renderer.is_behavior_shown;
+                    }
+                    Keycode::KP_PLUS => {
+                        self.speed = (self.speed * 2).min(MAX_SPEED);
+                    }
+                    Keycode::KP_MINUS => {
+                        self.speed = (self.speed / 2).max(1);
                     }
                     Keycode::SPACE => {
                         self.paused = if self.paused { false} else {true};
@@ -164,6 +238,13 @@ impl<'ttf> Game<'ttf> {
             // Left click
             /////////////////
             if let Event::MouseButtonDown { x, y, mouse_btn: MouseButton::Left, .. } = event {
+                // Start a designation, unless a slider is being dragged
+                if self.inputs.tool.is_some() && self.inputs.dragging_slider_id.is_none() {
+                    let tile = self.renderer.screen_to_tile_coords((x, y));
+                    self.inputs.selection_start = Some(tile);
+                    self.inputs.mouse_tile = tile;
+                }
+
                 // self.ant_manager.ants.append(&mut AntManager::generate_colony((x, y, self.renderer.camera.2), 5));
                 let (nx, ny) = self.renderer.screen_to_tile_coords((x, y));
                 let b = Biome::get_biome_params(
@@ -182,6 +263,16 @@ impl<'ttf> Game<'ttf> {
                 );
             }
 
+            /////////////////
+            // Right click
+            /////////////////
+            if let Event::MouseButtonDown { x, y, mouse_btn: MouseButton::Right, .. } = event {
+                let (nx, ny) = self.renderer.screen_to_tile_coords((x, y));
+                if let Some(player) = self.ant_manager.player_mut() {
+                    player.cancel_designation((nx, ny, self.renderer.camera.2));
+                }
+            }
+
             /////////////////
             // Mouse Wheel
             /////////////////
@@ -1,19 +1,20 @@
 use std::{ sync::{ Arc, Mutex }, time::{ Duration, Instant } };
 
-use ant::{ AntManager };
+use ant::{ ai::Difficulty, AntManager };
 use chunk::{ ChunkManager };
 use inputs::Inputs;
 use interface::Interface;
 use renderer::{ Renderer };
 use sdl2::{ event::Event, pixels::Color, ttf::Sdl2TtfContext, Sdl };
 
-use crate::ant::colony::Colony;
-
 //  ------
 mod debug;
 mod interface;
 //  ------
 
+// Command line tools
+mod export;
+
 mod time;
 // Chunks
 mod chunk;
@@ -34,11 +35,15 @@ pub struct Game<'ttf> {
     pub tps: Arc<Mutex<u64>>,
     pub fps: Arc<Mutex<u32>>,
     pub ticks: u64,
+    // In game ticks since the game started (never reset)
+    pub elapsed_ticks: u64,
     pub frames: u32,
     pub last_frame: Instant,
     pub last_tick: Instant,
     pub first_tick: Instant,
     pub tick_rate: Duration,
+    /// Ticks run each frame
+    pub speed: u64,
 
     // Chunk
     pub ant_manager: AntManager,
@@ -52,12 +57,13 @@ pub struct Game<'ttf> {
 }
 
 impl<'ttf> Game<'ttf> {
-    pub fn new(sdl: Sdl, ttf_context: &'ttf Sdl2TtfContext) -> Game<'ttf> {
+    pub fn new(sdl: Sdl, ttf_context: &'ttf Sdl2TtfContext, difficulty: Difficulty) -> Game<'ttf> {
         let renderer = Renderer::new(&sdl, &ttf_context, "Ants Layer").expect(
             "Failed to create game renderer"
         );
 
-        let ant_manager = AntManager::new();
+        let chunk_manager = ChunkManager::default();
+        let ant_manager = AntManager::new(chunk_manager.seed, difficulty);
 
         Game {
             // if set to false, the program will exit
@@ -69,15 +75,17 @@ impl<'ttf> Game<'ttf> {
             tps: Default::default(),
             fps: Default::default(),
             ticks: Default::default(),
+            elapsed_ticks: Default::default(),
             frames: Default::default(),
 
             last_frame: Instant::now(),
             last_tick: Instant::now(),
             first_tick: Instant::now(),
             tick_rate: Duration::from_secs_f64(1.0 / 60.0),
+            speed: 1,
 
             ant_manager,
-            chunk_manager: ChunkManager::default(),
+            chunk_manager,
             renderer,
 
             sdl,
@@ -100,7 +108,15 @@ impl<'ttf> Game<'ttf> {
         self.update_tps();
 
         // Let the ants think !
-        self.ant_manager.tick(&self.chunk_manager);
+        self.ant_manager.tick(&self.chunk_manager, self.elapsed_ticks);
+        if self.ant_manager.is_game_over() {
+            println!("Your queen is dead, game over !");
+            self.running = false;
+        }
+        // Plants grow back
+        self.chunk_manager.tick(self.elapsed_ticks);
+
+        self.elapsed_ticks += 1;
         self.last_tick = Instant::now();
     }
     #[allow(unused)]
@@ -140,7 +156,7 @@ impl<'ttf> Game<'ttf> {
 
         self.chunk_manager.render(&mut self.renderer, timestamp);
         self.ant_manager.render(&mut self.renderer, timestamp);
-
+        self.render_selection();
 
         // Top-left info display
         #[cfg(test)]
@@ -152,6 +168,17 @@ impl<'ttf> Game<'ttf> {
         self.last_frame = Instant::now();
     }
 
+    /// Area being dragged for a designation
+    fn render_selection(&mut self) {
+        let Some(area) = self.inputs.selection(self.renderer.camera.2) else {
+            return;
+        };
+        for (x, y, _z) in area.tiles() {
+            let (sx, sy) = self.renderer.tile_to_screen_coords((x, y));
+            self.renderer.rect((sx, sy), Color::WHITE);
+        }
+    }
+
     pub fn run(&mut self) {
         // Boucle de jeu
         while self.running {
@@ -170,7 +197,9 @@ impl<'ttf> Game<'ttf> {
             }
 
             if !self.paused {
-                self.tick();
+                for _ in 0..self.speed {
+                    self.tick();
+                }
             }
 
             // Maybe multithread will be needed for chunks rendering
@@ -183,8 +212,18 @@ impl<'ttf> Game<'ttf> {
 }
 
 fn main() -> Result<(), ()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("export") {
+        return export::run(&args[2..]).map_err(|e| eprintln!("Export failed : {}", e));
+    }
+
     let ttf_context = sdl2::ttf::init().expect("TTF init failed");
-    let mut game = Game::new(sdl2::init().unwrap(), &ttf_context);
+    // ants_layer [easy|normal|hard]
+    let difficulty = match args.get(1) {
+        Some(arg) => arg.parse().map_err(|e| eprintln!("{}", e))?,
+        None => Difficulty::default(),
+    };
+    let mut game = Game::new(sdl2::init().unwrap(), &ttf_context, difficulty);
 
     game.run();
 
@@ -67,6 +67,8 @@ pub struct Renderer<'ttf> {
     pub view_distance: i32,
     pub dims: (u32, u32),
     pub is_grid_enabled: bool,
+    /// Debug view of the behavior tree task of each ant
+    pub is_behavior_shown: bool,
     pub font: Font<'ttf, 'static>,
     // Width of a renderer tile (in pixels)
     pub tile_size: usize,
@@ -115,6 +117,7 @@ impl<'ttf> Renderer<'ttf> {
         Ok(Renderer::<'ttf> {
             font,
             is_grid_enabled: IS_GRID_ENABLED,
+            is_behavior_shown: false,
             camera_speed: 10.0,
             canvas,
             camera: (
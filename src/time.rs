@@ -1,9 +1,40 @@
-const TICKS_PER_MINUTE: u64 = 60;
+pub const TICKS_PER_MINUTE: u64 = 60;
 const MINUTES_PER_HOUR: u64 = 60;
 const HOURS_PER_DAY: u64 = 24;
 const MINUTES_PER_DAY: u64 = HOURS_PER_DAY * MINUTES_PER_HOUR;
-#[allow(unused)]
 const TICKS_PER_DAY: u64 = TICKS_PER_MINUTE * MINUTES_PER_DAY;
+/// Seasons are short, so long games see a few years
+const DAYS_PER_SEASON: u64 = 2;
+const TICKS_PER_SEASON: u64 = TICKS_PER_DAY * DAYS_PER_SEASON;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+/// Season at this game tick, the game starts in spring
+pub fn season(tick: u64) -> Season {
+    match (tick / TICKS_PER_SEASON) % 4 {
+        0 => Season::Spring,
+        1 => Season::Summer,
+        2 => Season::Autumn,
+        _ => Season::Winter,
+    }
+}
+
+/// Years since the game started
+pub fn year(tick: u64) -> u64 {
+    tick / (TICKS_PER_SEASON * 4)
+}
+
+/// First tick of this season, in this year
+#[cfg(test)]
+pub fn start_of(season: Season, year: u64) -> u64 {
+    (year * 4 + (season as u64)) * TICKS_PER_SEASON
+}
 
 /// (day, hour, minute)
 pub fn game_time(timestamp: f64) -> (u64, u64, u64) {